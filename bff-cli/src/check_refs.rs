@@ -0,0 +1,48 @@
+use std::io;
+use std::path::PathBuf;
+
+use bff::bigfile::deps::check_references;
+use bff::bigfile::platforms::Platform;
+use bff::names::NameContext;
+use serde_json::{json, to_writer_pretty};
+
+use crate::error::{BffCliError, BffCliResult};
+use crate::extract::{read_bigfile, read_names};
+
+pub fn check_refs(
+    bigfile_paths: &[PathBuf],
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    // The BigFiles share one name table so references resolve across them
+    let name_context = NameContext::default();
+    let bigfiles = bigfile_paths
+        .iter()
+        .enumerate()
+        .map(|(i, bigfile_path)| {
+            match i {
+                0 => read_names(bigfile_path, in_names, &name_context)?,
+                _ => read_names(bigfile_path, &Vec::new(), &name_context)?,
+            }
+            read_bigfile(bigfile_path, platform, &name_context, None)
+        })
+        .collect::<BffCliResult<Vec<_>>>()?;
+
+    let unresolved = check_references(&bigfiles);
+    let count = unresolved
+        .iter()
+        .map(|class| class.references.len())
+        .sum::<usize>();
+
+    let report = json!({
+        "bigfiles": bigfile_paths,
+        "unresolved": bigfiles[0].name_context.serialize(&unresolved),
+    });
+    to_writer_pretty(io::stdout().lock(), &report)?;
+
+    // Fail so the command can gate a build
+    match count {
+        0 => Ok(()),
+        count => Err(BffCliError::UnresolvedReferences { count }),
+    }
+}
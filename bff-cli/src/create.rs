@@ -1,29 +1,68 @@
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{self, BufWriter};
 use std::path::{Path, PathBuf};
 
+use bff::bigfile::compression::CompressionPolicy;
+use bff::bigfile::versions::Version;
 use bff::bigfile::BigFile;
+use bff::names::NameContext;
 use bff::BufReader;
+use clap::Args;
+use serde::de::DeserializeSeed;
+use serde_json::to_writer_pretty;
 
-use crate::error::BffCliResult;
+use crate::error::{BffCliError, BffCliResult};
 use crate::extract::{read_names, write_names};
+use crate::progress::block_progress;
+
+#[derive(Args)]
+pub struct CompressionArgs {
+    #[arg(
+        long,
+        help = "Class whose objects are always compressed, by string or hash"
+    )]
+    compress_class: Vec<String>,
+    #[arg(
+        long,
+        help = "Class whose objects are never compressed, by string or hash"
+    )]
+    no_compress_class: Vec<String>,
+    #[arg(long, help = "Objects smaller than this many bytes are not compressed")]
+    min_compress_size: Option<usize>,
+}
 
 pub fn create(
     directory: &Path,
     bigfile_path: &Path,
     in_names: &Vec<PathBuf>,
     out_names: &Option<PathBuf>,
+    roots: &[String],
+    max_block_size: &Option<usize>,
+    compression: &CompressionArgs,
 ) -> BffCliResult<()> {
-    read_names(bigfile_path, in_names)?;
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
 
     let manifest_path = directory.join("manifest.json");
     let manifest_reader = BufReader::new(File::open(manifest_path)?);
-    let manifest = serde_json::from_reader(manifest_reader)?;
+    let mut manifest: serde_json::Value = serde_json::from_reader(manifest_reader)?;
+
+    // Without blocks the layout is planned once the resources are read
+    let plan_layout = manifest.get("blocks").is_none();
+    if plan_layout {
+        manifest["blocks"] = serde_json::Value::Array(Vec::new());
+    }
+
+    // Names in the manifest are hashed with the hash function of its version
+    let version: Version = serde_json::from_value(manifest["version"].clone())?;
+    let name_context = name_context.with_name_type((&version).try_into()?);
 
     let mut bigfile = BigFile {
-        manifest,
+        manifest: name_context.deserialize().deserialize(manifest)?,
         objects: Default::default(),
+        name_context,
     };
+    let _names = bigfile.name_context.enter();
 
     let resources_path = directory.join("resources");
     std::fs::create_dir_all(&resources_path)?;
@@ -37,10 +76,43 @@ pub fn create(
         }
     }
 
+    if plan_layout {
+        // Names are matched by their display form so both strings and hashes work
+        let root_names = roots
+            .iter()
+            .map(|root| {
+                bigfile
+                    .objects
+                    .keys()
+                    .find(|name| name.to_string() == *root)
+                    .copied()
+                    .ok_or_else(|| BffCliError::UnknownObject { name: root.clone() })
+            })
+            .collect::<BffCliResult<Vec<_>>>()?;
+        bigfile.plan_layout(&root_names, *max_block_size)?;
+    }
+
+    // Classes are matched by their display form like the roots, a class without objects has
+    // nothing to apply to
+    let mut policy = CompressionPolicy {
+        min_size: compression.min_compress_size,
+        ..Default::default()
+    };
+    for resource in bigfile.objects.values() {
+        let class_name = resource.class_name.to_string();
+        if compression.compress_class.contains(&class_name) {
+            policy.classes.insert(resource.class_name, true);
+        } else if compression.no_compress_class.contains(&class_name) {
+            policy.classes.insert(resource.class_name, false);
+        }
+    }
+    bigfile.apply_compression_policy(&policy);
+
     let mut bigfile_writer = BufWriter::new(File::create(bigfile_path)?);
-    bigfile.write(&mut bigfile_writer, None)?;
+    let stats = bigfile.write_with_stats(&mut bigfile_writer, None, block_progress())?;
+    to_writer_pretty(io::stdout().lock(), &stats)?;
 
-    write_names(out_names)?;
+    write_names(out_names, &bigfile.name_context)?;
 
     Ok(())
 }
@@ -0,0 +1,71 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use bff::bigfile::platforms::Platform;
+use bff::names::NameContext;
+use clap::ValueEnum;
+use serde_json::to_writer_pretty;
+
+use crate::error::{BffCliError, BffCliResult};
+use crate::extract::{read_bigfile, read_names};
+
+#[derive(ValueEnum, Clone)]
+pub enum DepsFormat {
+    Dot,
+    Json,
+}
+
+pub fn deps(
+    bigfile_path: &Path,
+    format: &DepsFormat,
+    object: &Option<String>,
+    reverse: &bool,
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
+    let bigfile = read_bigfile(bigfile_path, platform, &name_context, None)?;
+
+    let graph = bigfile.dependency_graph();
+    let _names = bigfile.name_context.enter();
+    let mut stdout = io::stdout().lock();
+
+    if let Some(object) = object {
+        // Names are matched by their display form so both strings and hashes work
+        let name = graph
+            .nodes
+            .iter()
+            .map(|node| node.name)
+            .chain(graph.missing().map(|(name, _)| *name))
+            .find(|name| name.to_string() == *object)
+            .ok_or_else(|| BffCliError::UnknownObject {
+                name: object.clone(),
+            })?;
+
+        let names = if *reverse {
+            graph.referenced_by(&name).to_vec()
+        } else {
+            graph
+                .node(&name)
+                .map(|node| [node.references.as_slice(), node.missing.as_slice()].concat())
+                .unwrap_or_default()
+        };
+
+        match format {
+            DepsFormat::Json => to_writer_pretty(stdout, &names)?,
+            DepsFormat::Dot => {
+                for name in names {
+                    writeln!(stdout, "{}", name)?;
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    match format {
+        DepsFormat::Dot => graph.write_dot(&mut stdout)?,
+        DepsFormat::Json => to_writer_pretty(stdout, &graph)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bff::bigfile::platforms::Platform;
+use bff::names::NameContext;
+use serde_json::to_writer_pretty;
+
+use crate::error::BffCliResult;
+use crate::extract::{read_bigfile, read_names};
+
+pub fn diff(
+    bigfile_path: &Path,
+    other_bigfile_path: &Path,
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
+    read_names(other_bigfile_path, &Vec::new(), &name_context)?;
+
+    let bigfile = read_bigfile(bigfile_path, platform, &name_context, None)?;
+    let other_bigfile = read_bigfile(other_bigfile_path, platform, &name_context, None)?;
+
+    let diff = bigfile.diff(&other_bigfile);
+    to_writer_pretty(io::stdout().lock(), &bigfile.name_context.serialize(&diff))?;
+    Ok(())
+}
@@ -16,6 +16,27 @@ pub enum BffCliError {
         min_filler_length: usize,
         max_filler_length: usize,
     },
+    #[display(fmt = "{} blocks have a bad checksum", "count")]
+    ChecksumMismatches {
+        count: usize,
+    },
+    #[display(fmt = "No object named {} in the BigFile", "name")]
+    UnknownObject {
+        name: String,
+    },
+    #[display(
+        fmt = "The compressor parameters of {} streams cannot be detected",
+        "algorithm"
+    )]
+    UndetectableParameters {
+        algorithm: &'static str,
+    },
+    #[display(fmt = "No compressor parameters reproduce the stream")]
+    UndetectedParameters,
+    #[display(fmt = "{} unresolved references", "count")]
+    UnresolvedReferences {
+        count: usize,
+    },
 }
 
 pub type BffCliResult<T> = Result<T, BffCliError>;
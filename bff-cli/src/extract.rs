@@ -1,14 +1,26 @@
 use std::fs::File;
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use bff::bigfile::platforms::{try_platform_style_to_name_extension, Platform};
+use bff::bigfile::probe::probe;
+use bff::bigfile::progress::ProgressObserver;
+use bff::bigfile::salvage::SalvagedBigFile;
 use bff::bigfile::BigFile;
+use bff::names::{NameContext, NameFormat};
 use bff::BufReader;
+use clap::Args;
 
 use crate::error::BffCliResult;
+use crate::progress::block_progress;
 
-pub fn read_names(bigfile_path: &Path, in_names: &Vec<PathBuf>) -> BffCliResult<()> {
+// Add the strings of the BigFile's name file and of the input name files to the context
+pub fn read_names(
+    bigfile_path: &Path,
+    in_names: &Vec<PathBuf>,
+    name_context: &NameContext,
+) -> BffCliResult<()> {
     // Read the associated name file if it exists
     if let Some(extension) = bigfile_path.extension() {
         let name_extension =
@@ -17,7 +29,7 @@ pub fn read_names(bigfile_path: &Path, in_names: &Vec<PathBuf>) -> BffCliResult<
 
         if let Ok(f) = File::open(in_name) {
             let mut reader = BufReader::new(f);
-            bff::names::names().lock().unwrap().read(&mut reader)?;
+            name_context.names.write().unwrap().read(&mut reader)?;
         }
     }
 
@@ -25,30 +37,116 @@ pub fn read_names(bigfile_path: &Path, in_names: &Vec<PathBuf>) -> BffCliResult<
     for in_name in in_names {
         let f = File::open(in_name)?;
         let mut reader = BufReader::new(f);
-        bff::names::names().lock().unwrap().read(&mut reader)?;
+        name_context.names.write().unwrap().read(&mut reader)?;
     }
 
     Ok(())
 }
 
-pub fn write_names(out_names: &Option<PathBuf>) -> BffCliResult<()> {
+pub fn write_names(out_names: &Option<PathBuf>, name_context: &NameContext) -> BffCliResult<()> {
     if let Some(out_name) = out_names {
         let f = File::create(out_name)?;
         let mut writer = BufWriter::new(f);
-        bff::names::names().lock().unwrap().write(&mut writer)?;
+        name_context
+            .names
+            .read()
+            .unwrap()
+            .write(&mut writer, name_context.name_type)?;
     }
 
     Ok(())
 }
 
-pub fn read_bigfile(bigfile_path: &Path) -> BffCliResult<BigFile> {
-    let platform = bigfile_path
-        .extension()
-        .and_then(|e| e.try_into().ok())
-        .unwrap_or(Platform::PC);
+pub fn extension_platform(bigfile_path: &Path) -> Option<Platform> {
+    bigfile_path.extension().and_then(|e| e.try_into().ok())
+}
+
+pub fn read_bigfile(
+    bigfile_path: &Path,
+    platform: &Option<Platform>,
+    name_context: &NameContext,
+    observer: Option<Arc<dyn ProgressObserver>>,
+) -> BffCliResult<BigFile> {
     let f = File::open(bigfile_path)?;
     let mut reader = BufReader::new(f);
-    Ok(BigFile::read_platform(&mut reader, platform)?)
+    match platform {
+        Some(platform) => Ok(BigFile::read_platform(
+            &mut reader,
+            *platform,
+            name_context,
+            observer,
+        )?),
+        None => Ok(BigFile::read_auto(
+            &mut reader,
+            extension_platform(bigfile_path),
+            name_context,
+            observer,
+        )?),
+    }
+}
+
+pub fn read_bigfile_salvage(
+    bigfile_path: &Path,
+    platform: &Option<Platform>,
+    name_context: &NameContext,
+    observer: Option<Arc<dyn ProgressObserver>>,
+) -> BffCliResult<SalvagedBigFile> {
+    let f = File::open(bigfile_path)?;
+    let mut reader = BufReader::new(f);
+    match platform {
+        Some(platform) => Ok(BigFile::read_salvage_platform(
+            &mut reader,
+            *platform,
+            name_context,
+            observer,
+        )?),
+        None => Ok(BigFile::read_salvage_auto(
+            &mut reader,
+            extension_platform(bigfile_path),
+            name_context,
+            observer,
+        )?),
+    }
+}
+
+pub fn read_bigfile_with_common(
+    bigfile_path: &Path,
+    common_path: &Option<PathBuf>,
+    platform: &Option<Platform>,
+    name_context: &NameContext,
+    observer: Option<Arc<dyn ProgressObserver>>,
+) -> BffCliResult<BigFile> {
+    let Some(common_path) = common_path else {
+        return read_bigfile(bigfile_path, platform, name_context, observer);
+    };
+
+    let mut reader = BufReader::new(File::open(bigfile_path)?);
+    let platform = match platform {
+        Some(platform) => *platform,
+        None => probe(&mut reader, extension_platform(bigfile_path))?.1,
+    };
+    let mut common_reader = BufReader::new(File::open(common_path)?);
+    Ok(BigFile::read_platform_with_common(
+        &mut reader,
+        &mut common_reader,
+        platform,
+        name_context,
+        observer,
+    )?)
+}
+
+#[derive(Args)]
+pub struct ExtractArgs {
+    #[arg(
+        long,
+        help = "Skip blocks that fail to parse and list them in salvage.json"
+    )]
+    salvage: bool,
+    #[arg(
+        long,
+        help = "Write names as their hash and string so they do not depend on the names known when creating"
+    )]
+    explicit_names: bool,
 }
 
 pub fn extract(
@@ -56,20 +154,58 @@ pub fn extract(
     directory: &Path,
     in_names: &Vec<PathBuf>,
     out_names: &Option<PathBuf>,
+    common_path: &Option<PathBuf>,
+    platform: &Option<Platform>,
+    options: &ExtractArgs,
 ) -> BffCliResult<()> {
-    read_names(bigfile_path, in_names)?;
-
-    let bigfile = read_bigfile(bigfile_path)?;
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
+    let observer = block_progress();
+
+    let (mut bigfile, failures) = if options.salvage {
+        let SalvagedBigFile {
+            mut bigfile,
+            failures,
+        } = read_bigfile_salvage(bigfile_path, platform, &name_context, observer.clone())?;
+        if let Some(common_path) = common_path {
+            let common = read_bigfile(
+                common_path,
+                &Some(bigfile.manifest.platform),
+                &name_context,
+                observer,
+            )?;
+            bigfile.resolve_common(&common)?;
+        }
+        (bigfile, Some(failures))
+    } else {
+        let bigfile =
+            read_bigfile_with_common(bigfile_path, common_path, platform, &name_context, observer)?;
+        (bigfile, None)
+    };
+    bigfile.name_context.name_format = match options.explicit_names {
+        true => NameFormat::HashAndString,
+        false => NameFormat::StringOrHash,
+    };
 
     std::fs::create_dir(directory)?;
 
     let manifest_path = directory.join("manifest.json");
     let manifest_writer = BufWriter::new(File::create(manifest_path)?);
-    serde_json::to_writer_pretty(manifest_writer, &bigfile.manifest)?;
+    serde_json::to_writer_pretty(
+        manifest_writer,
+        &bigfile.name_context.serialize(&bigfile.manifest),
+    )?;
+
+    if let Some(failures) = failures {
+        let salvage_path = directory.join("salvage.json");
+        let salvage_writer = BufWriter::new(File::create(salvage_path)?);
+        serde_json::to_writer_pretty(salvage_writer, &failures)?;
+    }
 
     let resources_path = directory.join("resources");
     std::fs::create_dir(&resources_path)?;
 
+    let _names = bigfile.name_context.enter();
     for resource in bigfile.objects.values() {
         let name = resource.name;
         let class_name = resource.class_name;
@@ -78,7 +214,7 @@ pub fn extract(
         bigfile.dump_resource(resource, &mut writer)?;
     }
 
-    write_names(out_names)?;
+    write_names(out_names, &bigfile.name_context)?;
 
     Ok(())
 }
@@ -0,0 +1,50 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use bff::bigfile::platforms::Platform;
+use bff::names::NameContext;
+use serde_json::to_writer_pretty;
+
+use crate::error::{BffCliError, BffCliResult};
+use crate::extract::{read_bigfile, read_names};
+
+pub fn gc(
+    bigfile_path: &Path,
+    output_bigfile_path: &Option<PathBuf>,
+    roots: &[String],
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
+    let mut bigfile = read_bigfile(bigfile_path, platform, &name_context, None)?;
+
+    let mut root_names = bigfile.default_roots();
+    {
+        let _names = bigfile.name_context.enter();
+        for root in roots {
+            // Names are matched by their display form so both strings and hashes work
+            let name = bigfile
+                .objects
+                .keys()
+                .find(|name| name.to_string() == *root)
+                .ok_or_else(|| BffCliError::UnknownObject { name: root.clone() })?;
+            root_names.push(*name);
+        }
+    }
+
+    let report = bigfile.find_orphans(&root_names);
+    {
+        let _names = bigfile.name_context.enter();
+        to_writer_pretty(io::stdout().lock(), &report)?;
+    }
+
+    if let Some(output_bigfile_path) = output_bigfile_path {
+        bigfile.strip_orphans(&report);
+        let mut writer = BufWriter::new(File::create(output_bigfile_path)?);
+        bigfile.write(&mut writer, None, None)?;
+    }
+
+    Ok(())
+}
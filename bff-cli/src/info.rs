@@ -1,15 +1,38 @@
+use std::fs::File;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use bff::bigfile::lazy::LazyBigFile;
+use bff::bigfile::platforms::Platform;
+use bff::names::NameContext;
+use bff::BufReader;
 use serde_json::to_writer_pretty;
 
 use crate::error::BffCliResult;
-use crate::extract::{read_bigfile, read_names};
+use crate::extract::{extension_platform, read_names};
 
-pub fn info(bigfile_path: &Path, in_names: &Vec<PathBuf>) -> BffCliResult<()> {
-    read_names(bigfile_path, in_names)?;
+pub fn info(
+    bigfile_path: &Path,
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
 
-    let bigfile = read_bigfile(bigfile_path)?;
-    to_writer_pretty(io::stdout().lock(), &bigfile)?;
+    let reader = BufReader::new(File::open(bigfile_path)?);
+    // Only the index is needed, objects are never decompressed
+    let bigfile = match platform {
+        Some(platform) => LazyBigFile::read_platform(reader, *platform, &name_context, None)?,
+        None => LazyBigFile::read_auto(
+            reader,
+            extension_platform(bigfile_path),
+            &name_context,
+            None,
+        )?,
+    };
+    to_writer_pretty(
+        io::stdout().lock(),
+        &bigfile.index.name_context.serialize(bigfile.manifest()),
+    )?;
     Ok(())
 }
@@ -1,6 +1,7 @@
 use std::fs::File;
 use std::io::{self, BufWriter, Cursor, Read, Write};
 
+use bff::bigfile::compression::CompressionParameters;
 use bff::lz::{
     arcode_compress_data_with_header_writer,
     arcode_decompress_data_with_header_parser,
@@ -8,17 +9,19 @@ use bff::lz::{
     gzip_decompress_data_with_header_parser,
     lz4_compress_data_with_header_writer,
     lz4_decompress_data_with_header_parser,
+    lz4_detect_mode,
     lzo_compress,
     lzo_decompress,
     lzrs_compress_data_with_header_writer,
     lzrs_decompress_data_with_header_parser,
     zlib_compress_data_with_header_writer,
     zlib_decompress_data_with_header_parser,
+    zlib_detect_level,
 };
 use bff::{BufReader, Endian};
 use clap::ValueEnum;
 
-use crate::error::BffCliResult;
+use crate::error::{BffCliError, BffCliResult};
 use crate::stdio_or_path::StdioOrPath;
 
 #[derive(ValueEnum, Clone, Copy)]
@@ -37,6 +40,19 @@ pub enum LzAlgorithm {
     Gzip,
 }
 
+impl LzAlgorithm {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Lzrs => "LZRS",
+            Self::Lzo => "LZO",
+            Self::Lz4 => "LZ4",
+            Self::Arcode => "arithmetic coded",
+            Self::Zlib => "zlib",
+            Self::Gzip => "gzip",
+        }
+    }
+}
+
 fn lz_internal<R: Read, W: Write>(
     uncompressed: &mut R,
     compressed: &mut W,
@@ -97,16 +113,47 @@ pub fn lz(
     }
 }
 
+// The compression field of a manifest whose streams are written like this one, the stream starts
+// with its size fields
+fn detect_parameters(
+    compressed: &[u8],
+    decompressed: &[u8],
+    algorithm: LzAlgorithm,
+) -> BffCliResult<CompressionParameters> {
+    let stream = compressed.get(8..).unwrap_or_default();
+    let parameters = match algorithm {
+        LzAlgorithm::Lz4 => {
+            lz4_detect_mode(decompressed, stream).map(|mode| CompressionParameters {
+                lz4: Some(mode),
+                ..Default::default()
+            })
+        }
+        LzAlgorithm::Zlib => {
+            zlib_detect_level(decompressed, stream).map(|level| CompressionParameters {
+                zlib_level: Some(level),
+                ..Default::default()
+            })
+        }
+        _ => {
+            return Err(BffCliError::UndetectableParameters {
+                algorithm: algorithm.name(),
+            })
+        }
+    };
+    parameters.ok_or(BffCliError::UndetectedParameters)
+}
+
 fn unlz_internal<R: Read, W: Write>(
     compressed: &mut R,
     uncompressed: &mut W,
     endian: Endian,
     algorithm: LzAlgorithm,
+    detect: bool,
 ) -> BffCliResult<()> {
     let mut buf: Vec<u8> = Vec::new();
     compressed.read_to_end(&mut buf)?;
 
-    let mut reader = BufReader::new(Cursor::new(buf));
+    let mut reader = BufReader::new(Cursor::new(buf.as_slice()));
 
     let decompressed = match algorithm {
         LzAlgorithm::Lzrs => lzrs_decompress_data_with_header_parser(&mut reader, endian)?,
@@ -121,7 +168,12 @@ fn unlz_internal<R: Read, W: Write>(
         LzAlgorithm::Gzip => gzip_decompress_data_with_header_parser(&mut reader, endian)?,
     };
 
-    uncompressed.write_all(&decompressed)?;
+    if detect {
+        let parameters = detect_parameters(&buf, &decompressed, algorithm)?;
+        serde_json::to_writer_pretty(uncompressed, &parameters)?;
+    } else {
+        uncompressed.write_all(&decompressed)?;
+    }
     Ok(())
 }
 
@@ -130,33 +182,41 @@ pub fn unlz(
     uncompressed: &StdioOrPath,
     endian: &LzEndian,
     algorithm: &LzAlgorithm,
+    detect: &bool,
 ) -> BffCliResult<()> {
     let endian = match endian {
         LzEndian::Big => Endian::Big,
         LzEndian::Little => Endian::Little,
     };
     let algorithm = *algorithm;
+    let detect = *detect;
 
     match (compressed, uncompressed) {
         (StdioOrPath::Stdio, StdioOrPath::Stdio) => {
             let stdin = io::stdin();
             let stdout = io::stdout();
-            unlz_internal(&mut stdin.lock(), &mut stdout.lock(), endian, algorithm)
+            unlz_internal(
+                &mut stdin.lock(),
+                &mut stdout.lock(),
+                endian,
+                algorithm,
+                detect,
+            )
         }
         (StdioOrPath::Stdio, StdioOrPath::Path(output_path)) => {
             let stdin = io::stdin();
             let mut output = BufWriter::new(File::create(output_path)?);
-            unlz_internal(&mut stdin.lock(), &mut output, endian, algorithm)
+            unlz_internal(&mut stdin.lock(), &mut output, endian, algorithm, detect)
         }
         (StdioOrPath::Path(input_path), StdioOrPath::Stdio) => {
             let mut input = BufReader::new(File::open(input_path)?);
             let stdout = io::stdout();
-            unlz_internal(&mut input, &mut stdout.lock(), endian, algorithm)
+            unlz_internal(&mut input, &mut stdout.lock(), endian, algorithm, detect)
         }
         (StdioOrPath::Path(input_path), StdioOrPath::Path(output_path)) => {
             let mut input = BufReader::new(File::open(input_path)?);
             let mut output = BufWriter::new(File::create(output_path)?);
-            unlz_internal(&mut input, &mut output, endian, algorithm)
+            unlz_internal(&mut input, &mut output, endian, algorithm, detect)
         }
     }
 }
@@ -1,25 +1,39 @@
 use std::path::PathBuf;
 
+use bff::bigfile::platforms::Platform;
 use clap::*;
 use crc::{CrcAlgorithm, CrcFormat, CrcMode};
 use error::BffCliResult;
 use lz::LzEndian;
 use reverse_crc32::DEFAULT_CHARACTER_SET;
 
+use crate::create::CompressionArgs;
+use crate::deps::DepsFormat;
+use crate::extract::ExtractArgs;
 use crate::lz::LzAlgorithm;
+use crate::merge::MergePolicy;
 
+mod check_refs;
 mod crc;
 mod create;
 mod csc;
+mod deps;
+mod diff;
 mod error;
 mod extract;
 mod fat_lin;
+mod gc;
 mod info;
 mod lz;
+mod merge;
+mod patch;
+mod progress;
 mod psc;
+mod retarget;
 mod reverse_crc32;
 mod round_trip;
 mod stdio_or_path;
+mod verify;
 
 use shadow_rs::shadow;
 
@@ -38,6 +52,15 @@ enum Commands {
         in_names: Vec<PathBuf>,
         #[arg(long)]
         out_names: Option<PathBuf>,
+        #[arg(long, help = "Common BigFile to resolve external objects from")]
+        common: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
+        #[command(flatten)]
+        options: ExtractArgs,
     },
     #[clap(alias = "c")]
     Create {
@@ -47,15 +70,156 @@ enum Commands {
         in_names: Vec<PathBuf>,
         #[arg(long)]
         out_names: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Object loaded first when the manifest has no blocks, by string or hash. Worlds and GameObjs are always roots"
+        )]
+        root: Vec<String>,
+        #[arg(
+            long,
+            help = "Largest working buffer a block may need when the manifest has no blocks, defaults to the limit of the version"
+        )]
+        max_block_size: Option<usize>,
+        #[command(flatten)]
+        compression: CompressionArgs,
     },
     #[clap(alias = "t")]
     Info {
         bigfile: PathBuf,
         #[arg(long)]
         in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
     },
     #[clap(alias = "rt")]
-    RoundTrip { bigfile: PathBuf },
+    RoundTrip {
+        bigfile: PathBuf,
+        #[arg(long, help = "Common BigFile to resolve external objects from")]
+        common: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    Diff {
+        bigfile: PathBuf,
+        other_bigfile: PathBuf,
+        #[arg(long)]
+        in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFiles, detected from their contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    CheckRefs {
+        #[arg(
+            required = true,
+            help = "BigFiles whose objects references are resolved against"
+        )]
+        bigfiles: Vec<PathBuf>,
+        #[arg(long)]
+        in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFiles, detected from their contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    Deps {
+        bigfile: PathBuf,
+        #[clap(value_enum)]
+        #[arg(short, long, default_value_t = DepsFormat::Dot)]
+        format: DepsFormat,
+        #[arg(
+            long,
+            help = "Only list the names referenced by this object, by string or hash"
+        )]
+        object: Option<String>,
+        #[arg(
+            long,
+            requires = "object",
+            help = "List the objects referencing the object instead"
+        )]
+        reverse: bool,
+        #[arg(long)]
+        in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    Gc {
+        bigfile: PathBuf,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Additional root object, by string or hash. Worlds and GameObjs are always roots"
+        )]
+        root: Vec<String>,
+        #[arg(long)]
+        in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    Merge {
+        output: PathBuf,
+        #[arg(required = true, num_args = 2.., help = "BigFiles to merge, in order")]
+        bigfiles: Vec<PathBuf>,
+        #[clap(value_enum)]
+        #[arg(long, default_value_t = MergePolicy::LastWins, help = "How objects present in several BigFiles are resolved")]
+        policy: MergePolicy,
+        #[arg(long)]
+        in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFiles, detected from their contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    Verify {
+        bigfile: PathBuf,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    #[clap(alias = "p")]
+    Patch {
+        bigfile: PathBuf,
+        #[arg(required = true, help = "Resources to replace in the BigFile")]
+        resources: Vec<PathBuf>,
+        #[arg(long)]
+        in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
+    },
+    Retarget {
+        bigfile: PathBuf,
+        output: PathBuf,
+        #[arg(help = "Platform to convert the BigFile to")]
+        target_platform: Platform,
+        #[arg(long)]
+        in_names: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Platform of the BigFile, detected from its contents by default"
+        )]
+        platform: Option<Platform>,
+    },
     Crc {
         string: Option<String>,
         #[arg(
@@ -102,6 +266,11 @@ enum Commands {
         #[clap(value_enum)]
         #[arg(short, long, default_value_t = LzAlgorithm::Lzrs)]
         algorithm: LzAlgorithm,
+        #[arg(
+            long,
+            help = "Output the compression field of a manifest that writes LZ4 or zlib streams like this one instead of the data"
+        )]
+        detect: bool,
     },
     Lz {
         uncompressed: StdioOrPath,
@@ -170,14 +339,81 @@ fn main() -> BffCliResult<()> {
             directory,
             in_names,
             out_names,
-        } => extract::extract(bigfile, directory, in_names, out_names),
+            common,
+            platform,
+            options,
+        } => extract::extract(
+            bigfile, directory, in_names, out_names, common, platform, options,
+        ),
         Commands::Create {
             directory,
             bigfile,
             in_names,
             out_names,
-        } => create::create(directory, bigfile, in_names, out_names),
-        Commands::Info { bigfile, in_names } => info::info(bigfile, in_names),
+            root,
+            max_block_size,
+            compression,
+        } => create::create(
+            directory,
+            bigfile,
+            in_names,
+            out_names,
+            root,
+            max_block_size,
+            compression,
+        ),
+        Commands::Info {
+            bigfile,
+            in_names,
+            platform,
+        } => info::info(bigfile, in_names, platform),
+        Commands::Diff {
+            bigfile,
+            other_bigfile,
+            in_names,
+            platform,
+        } => diff::diff(bigfile, other_bigfile, in_names, platform),
+        Commands::CheckRefs {
+            bigfiles,
+            in_names,
+            platform,
+        } => check_refs::check_refs(bigfiles, in_names, platform),
+        Commands::Deps {
+            bigfile,
+            format,
+            object,
+            reverse,
+            in_names,
+            platform,
+        } => deps::deps(bigfile, format, object, reverse, in_names, platform),
+        Commands::Gc {
+            bigfile,
+            output,
+            root,
+            in_names,
+            platform,
+        } => gc::gc(bigfile, output, root, in_names, platform),
+        Commands::Merge {
+            output,
+            bigfiles,
+            policy,
+            in_names,
+            platform,
+        } => merge::merge(bigfiles, output, policy, in_names, platform),
+        Commands::Verify { bigfile, platform } => verify::verify(bigfile, platform),
+        Commands::Patch {
+            bigfile,
+            resources,
+            in_names,
+            platform,
+        } => patch::patch(bigfile, resources, in_names, platform),
+        Commands::Retarget {
+            bigfile,
+            output,
+            target_platform,
+            in_names,
+            platform,
+        } => retarget::retarget(bigfile, output, target_platform, in_names, platform),
         Commands::Crc {
             string,
             starting,
@@ -190,7 +426,8 @@ fn main() -> BffCliResult<()> {
             uncompressed,
             endian,
             algorithm,
-        } => lz::unlz(compressed, uncompressed, endian, algorithm),
+            detect,
+        } => lz::unlz(compressed, uncompressed, endian, algorithm, detect),
         Commands::Lz {
             uncompressed,
             compressed,
@@ -212,7 +449,11 @@ fn main() -> BffCliResult<()> {
             max_filler_length,
             character_set,
         ),
-        Commands::RoundTrip { bigfile } => round_trip::round_trip(bigfile),
+        Commands::RoundTrip {
+            bigfile,
+            common,
+            platform,
+        } => round_trip::round_trip(bigfile, common, platform),
         Commands::Csc { input, output, key } => csc::csc(input, output, key),
         Commands::ExtractPsc {
             psc,
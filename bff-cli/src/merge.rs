@@ -0,0 +1,54 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use bff::bigfile::merge::ConflictPolicy;
+use bff::bigfile::platforms::Platform;
+use bff::names::NameContext;
+use clap::ValueEnum;
+
+use crate::error::BffCliResult;
+use crate::extract::{read_bigfile, read_names};
+
+#[derive(ValueEnum, Clone)]
+pub enum MergePolicy {
+    #[value(alias("first"))]
+    FirstWins,
+    #[value(alias("last"))]
+    LastWins,
+    Error,
+}
+
+impl From<&MergePolicy> for ConflictPolicy {
+    fn from(policy: &MergePolicy) -> Self {
+        match policy {
+            MergePolicy::FirstWins => ConflictPolicy::FirstWins,
+            MergePolicy::LastWins => ConflictPolicy::LastWins,
+            MergePolicy::Error => ConflictPolicy::Error,
+        }
+    }
+}
+
+pub fn merge(
+    bigfile_paths: &[PathBuf],
+    output_bigfile_path: &Path,
+    policy: &MergePolicy,
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let (first_path, other_paths) = bigfile_paths.split_first().unwrap();
+
+    let name_context = NameContext::default();
+    read_names(first_path, in_names, &name_context)?;
+    let mut bigfile = read_bigfile(first_path, platform, &name_context, None)?;
+
+    for other_path in other_paths {
+        read_names(other_path, &Vec::new(), &name_context)?;
+        let other = read_bigfile(other_path, platform, &name_context, None)?;
+        bigfile.merge(other, policy.into())?;
+    }
+
+    let mut writer = BufWriter::new(File::create(output_bigfile_path)?);
+    bigfile.write(&mut writer, None, None)?;
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::fs::{File, OpenOptions};
+use std::io::Seek;
+use std::path::{Path, PathBuf};
+
+use bff::bigfile::lazy::LazyBigFile;
+use bff::bigfile::platforms::Platform;
+use bff::bigfile::BigFile;
+use bff::names::NameContext;
+use bff::BufReader;
+
+use crate::error::BffCliResult;
+use crate::extract::{extension_platform, read_names};
+
+pub fn patch(
+    bigfile_path: &Path,
+    resource_paths: &Vec<PathBuf>,
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
+
+    // Only the index is needed to decode the replacement resources
+    let reader = BufReader::new(File::open(bigfile_path)?);
+    let index = match platform {
+        Some(platform) => LazyBigFile::read_platform(reader, *platform, &name_context, None)?,
+        None => LazyBigFile::read_auto(
+            reader,
+            extension_platform(bigfile_path),
+            &name_context,
+            None,
+        )?,
+    }
+    .index;
+
+    let mut resources = Vec::with_capacity(resource_paths.len());
+    for resource_path in resource_paths {
+        let mut resource_reader = BufReader::new(File::open(resource_path)?);
+        resources.push(index.read_resource(&mut resource_reader)?);
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(bigfile_path)?;
+    for resource in resources.iter() {
+        file.rewind()?;
+        BigFile::patch_platform(
+            &mut file,
+            index.manifest.platform,
+            &index.name_context,
+            resource,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::Arc;
+
+use bff::bigfile::progress::{Progress, ProgressObserver};
+
+struct BlockProgress;
+
+impl ProgressObserver for BlockProgress {
+    fn progress(&self, progress: Progress) {
+        if let Progress::Block { index, count } = progress {
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\rBlock {}/{}", index + 1, count);
+            if index + 1 == count {
+                let _ = writeln!(stderr);
+            }
+            let _ = stderr.flush();
+        }
+    }
+}
+
+// Show the block being read or written on stderr, only when stderr is a terminal so redirected
+// output is not cluttered
+pub fn block_progress() -> Option<Arc<dyn ProgressObserver>> {
+    io::stderr()
+        .is_terminal()
+        .then(|| Arc::new(BlockProgress) as Arc<dyn ProgressObserver>)
+}
@@ -0,0 +1,27 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use bff::bigfile::platforms::Platform;
+use bff::names::NameContext;
+
+use crate::error::BffCliResult;
+use crate::extract::{read_bigfile, read_names};
+
+pub fn retarget(
+    bigfile_path: &Path,
+    output_bigfile_path: &Path,
+    target_platform: &Platform,
+    in_names: &Vec<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let name_context = NameContext::default();
+    read_names(bigfile_path, in_names, &name_context)?;
+
+    let bigfile =
+        read_bigfile(bigfile_path, platform, &name_context, None)?.retarget(*target_platform)?;
+
+    let mut writer = BufWriter::new(File::create(output_bigfile_path)?);
+    bigfile.write(&mut writer, None, None)?;
+    Ok(())
+}
@@ -1,20 +1,32 @@
 use std::ffi::OsStr;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use bff::bigfile::platforms::Platform;
 use bff::bigfile::BigFile;
+use bff::names::NameContext;
 
 use crate::error::BffCliResult;
-use crate::extract::read_bigfile;
+use crate::extract::read_bigfile_with_common;
 
 pub fn write_bigfile(bigfile_path: &Path, bigfile: &BigFile) -> BffCliResult<()> {
     let mut writer = File::create(bigfile_path)?;
     // Intentionally use an unbuffered writer for debugging purposes.
-    Ok(BigFile::write(bigfile, &mut writer, None)?)
+    Ok(BigFile::write(bigfile, &mut writer, None, None)?)
 }
 
-pub fn round_trip(bigfile_path: &Path) -> BffCliResult<()> {
-    let bigfile = read_bigfile(bigfile_path)?;
+pub fn round_trip(
+    bigfile_path: &Path,
+    common_path: &Option<PathBuf>,
+    platform: &Option<Platform>,
+) -> BffCliResult<()> {
+    let bigfile = read_bigfile_with_common(
+        bigfile_path,
+        common_path,
+        platform,
+        &NameContext::default(),
+        None,
+    )?;
     let mut new_extension = bigfile_path
         .extension()
         .unwrap_or(OsStr::new(""))
@@ -0,0 +1,28 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use bff::bigfile::platforms::Platform;
+use bff::bigfile::BigFile;
+use bff::names::NameContext;
+use bff::BufReader;
+use serde_json::to_writer_pretty;
+
+use crate::error::{BffCliError, BffCliResult};
+use crate::extract::extension_platform;
+
+pub fn verify(bigfile_path: &Path, platform: &Option<Platform>) -> BffCliResult<()> {
+    let mut reader = BufReader::new(File::open(bigfile_path)?);
+    let name_context = NameContext::default();
+    let mismatches = match platform {
+        Some(platform) => BigFile::verify_platform(&mut reader, *platform, &name_context)?,
+        None => BigFile::verify_auto(&mut reader, extension_platform(bigfile_path), &name_context)?,
+    };
+
+    to_writer_pretty(io::stdout().lock(), &mismatches)?;
+
+    match mismatches.len() {
+        0 => Ok(()),
+        count => Err(BffCliError::ChecksumMismatches { count }),
+    }
+}
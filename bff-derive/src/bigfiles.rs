@@ -18,16 +18,48 @@ impl Parse for BffBigFileMacroInput {
 }
 
 pub fn derive_bigfiles(input: BffBigFileMacroInput) -> TokenStream {
-    let read_bigfile = impl_read_bigfile(&input);
+    let read_bigfile = impl_read_bigfile(
+        &input,
+        quote! { read },
+        quote! { read_platform },
+        quote! { Self },
+    );
+    let read_index = impl_read_bigfile(
+        &input,
+        quote! { read_index },
+        quote! { read_platform },
+        quote! { Self },
+    );
+    let read_salvage = impl_read_bigfile(
+        &input,
+        quote! { read_salvage },
+        quote! { read_salvage_platform },
+        quote! { crate::bigfile::salvage::SalvagedBigFile },
+    );
     let write_bigfile = impl_write_bigfile(&input);
+    let patch_bigfile = impl_patch_bigfile(&input);
+    let verify_bigfile = impl_verify_bigfile(&input);
     let dump_resource = impl_dump_resource(&input);
     let read_resource = impl_read_resource(&input);
     let version_into_name_type = impl_version_into_name_type(&input);
+    let is_implemented = impl_is_implemented(&input);
+    let max_block_size = impl_max_block_size(&input);
 
     quote! {
         impl BigFile {
             #read_bigfile
+            #read_salvage
             #write_bigfile
+            #patch_bigfile
+            #verify_bigfile
+            #dump_resource
+            #read_resource
+            #is_implemented
+            #max_block_size
+        }
+
+        impl crate::bigfile::lazy::BigFileIndex {
+            #read_index
             #dump_resource
             #read_resource
         }
@@ -36,7 +68,12 @@ pub fn derive_bigfiles(input: BffBigFileMacroInput) -> TokenStream {
     }
 }
 
-fn impl_read_bigfile(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream {
+fn impl_read_bigfile(
+    input: &BffBigFileMacroInput,
+    method: proc_macro2::TokenStream,
+    function: proc_macro2::TokenStream,
+    output: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
     let arms = input
         .forms
         .iter()
@@ -51,19 +88,20 @@ fn impl_read_bigfile(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream {
             quote! {
                 #(#attrs)*
                 #pat #guard => {
-                    crate::names::names().lock().unwrap().name_type = <#body as BigFileIo>::NAME_TYPE;
-                    <#body as BigFileIo>::read(reader, version, platform)
+                    let _names = name_context.with_name_type(<#body as BigFileIo>::NAME_TYPE).enter();
+                    <#body as BigFileIo>::#method(reader, version, platform)
                 }
             }
         })
         .collect::<Vec<_>>();
 
     quote! {
-        pub fn read_platform<R: std::io::Read + std::io::Seek>(reader: &mut R, platform: crate::bigfile::platforms::Platform) -> crate::BffResult<Self> {
+        pub fn #function<R: std::io::Read + std::io::Seek>(reader: &mut R, platform: crate::bigfile::platforms::Platform, name_context: &crate::names::NameContext, observer: Option<std::sync::Arc<dyn crate::bigfile::progress::ProgressObserver>>) -> crate::BffResult<#output> {
             use crate::bigfile::versions::Version::*;
             use crate::bigfile::platforms::Platform::*;
             use binrw::BinRead;
             use crate::traits::BigFileIo;
+            let _progress = crate::bigfile::progress::observe(observer);
             let endian: crate::Endian = platform.into();
             let version: crate::bigfile::versions::Version = crate::helpers::FixedStringNull::<256>::read_be(reader)?.as_str().into();
             match (version.clone(), platform) {
@@ -89,7 +127,9 @@ fn impl_write_bigfile(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream
             quote! {
                 #(#attrs)*
                 #pat #guard => {
-                    crate::names::names().lock().unwrap().name_type = <#body as BigFileIo>::NAME_TYPE;
+                    let name_context = self.name_context.with_name_type(<#body as BigFileIo>::NAME_TYPE);
+                    name_context.convert_all(self.objects.values().flat_map(|resource| [resource.name, resource.class_name]))?;
+                    let _names = name_context.enter();
                     <#body as BigFileIo>::write(self, writer, tag)
                 }
             }
@@ -97,11 +137,12 @@ fn impl_write_bigfile(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream
         .collect::<Vec<_>>();
 
     quote! {
-        pub fn write<W: std::io::Write + std::io::Seek>(&self, writer: &mut W, tag: Option<&str>) -> crate::BffResult<()> {
+        pub fn write<W: std::io::Write + std::io::Seek>(&self, writer: &mut W, tag: Option<&str>, observer: Option<std::sync::Arc<dyn crate::bigfile::progress::ProgressObserver>>) -> crate::BffResult<()> {
             use crate::bigfile::versions::Version::*;
             use crate::bigfile::platforms::Platform::*;
             use binrw::BinWrite;
             use crate::traits::BigFileIo;
+            let _progress = crate::bigfile::progress::observe(observer);
             let platform = self.manifest.platform;
             let endian: crate::Endian = platform.into();
             let version = &self.manifest.version;
@@ -115,6 +156,80 @@ fn impl_write_bigfile(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream
     }
 }
 
+fn impl_patch_bigfile(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream {
+    let arms = input
+        .forms
+        .iter()
+        .map(|form| {
+            let attrs = &form.attrs;
+            let pat = &form.pat;
+            let guard = match &form.guard {
+                Some((_, guard)) => quote! { #guard },
+                None => quote! {},
+            };
+            let body = &form.body;
+            quote! {
+                #(#attrs)*
+                #pat #guard => {
+                    let _names = name_context.with_name_type(<#body as BigFileIo>::NAME_TYPE).enter();
+                    <#body as BigFileIo>::patch(stream, version, platform, resource)
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    quote! {
+        pub fn patch_platform<S: std::io::Read + std::io::Write + std::io::Seek + crate::traits::SetLen>(stream: &mut S, platform: crate::bigfile::platforms::Platform, name_context: &crate::names::NameContext, resource: &crate::bigfile::resource::Resource) -> crate::BffResult<u64> {
+            use crate::bigfile::versions::Version::*;
+            use crate::bigfile::platforms::Platform::*;
+            use binrw::BinRead;
+            use crate::traits::BigFileIo;
+            let version: crate::bigfile::versions::Version = crate::helpers::FixedStringNull::<256>::read_be(stream)?.as_str().into();
+            match (version.clone(), platform) {
+                #(#arms)*
+                _ => Err(crate::error::UnimplementedVersionPlatformError::new(version, platform).into()),
+            }
+        }
+    }
+}
+
+fn impl_verify_bigfile(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream {
+    let arms = input
+        .forms
+        .iter()
+        .map(|form| {
+            let attrs = &form.attrs;
+            let pat = &form.pat;
+            let guard = match &form.guard {
+                Some((_, guard)) => quote! { #guard },
+                None => quote! {},
+            };
+            let body = &form.body;
+            quote! {
+                #(#attrs)*
+                #pat #guard => {
+                    let _names = name_context.with_name_type(<#body as BigFileIo>::NAME_TYPE).enter();
+                    <#body as BigFileIo>::verify(reader, version, platform)
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    quote! {
+        pub fn verify_platform<R: std::io::Read + std::io::Seek>(reader: &mut R, platform: crate::bigfile::platforms::Platform, name_context: &crate::names::NameContext) -> crate::BffResult<Vec<crate::bigfile::verify::ChecksumMismatch>> {
+            use crate::bigfile::versions::Version::*;
+            use crate::bigfile::platforms::Platform::*;
+            use binrw::BinRead;
+            use crate::traits::BigFileIo;
+            let version: crate::bigfile::versions::Version = crate::helpers::FixedStringNull::<256>::read_be(reader)?.as_str().into();
+            match (version.clone(), platform) {
+                #(#arms)*
+                _ => Err(crate::error::UnimplementedVersionPlatformError::new(version, platform).into()),
+            }
+        }
+    }
+}
+
 fn impl_dump_resource(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream {
     let arms = input
         .forms
@@ -130,7 +245,7 @@ fn impl_dump_resource(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream
             quote! {
                 #(#attrs)*
                 #pat #guard => {
-                    crate::names::names().lock().unwrap().name_type = <#body as BigFileIo>::NAME_TYPE;
+                    let _names = self.name_context.with_name_type(<#body as BigFileIo>::NAME_TYPE).enter();
                     Ok(<#body as BigFileIo>::ResourceType::dump_resource(resource, writer, endian)?)
                 }
             }
@@ -168,7 +283,7 @@ fn impl_read_resource(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream
             quote! {
                 #(#attrs)*
                 #pat #guard => {
-                    crate::names::names().lock().unwrap().name_type = <#body as BigFileIo>::NAME_TYPE;
+                    let _names = self.name_context.with_name_type(<#body as BigFileIo>::NAME_TYPE).enter();
                     Ok(<#body as BigFileIo>::ResourceType::read_resource(reader, endian)?)
                 }
             }
@@ -226,3 +341,65 @@ fn impl_version_into_name_type(input: &BffBigFileMacroInput) -> proc_macro2::Tok
         }
     }
 }
+
+fn impl_is_implemented(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream {
+    let arms = input
+        .forms
+        .iter()
+        .map(|form| {
+            let attrs = &form.attrs;
+            let pat = &form.pat;
+            let guard = match &form.guard {
+                Some((_, guard)) => quote! { #guard },
+                None => quote! {},
+            };
+            quote! {
+                #(#attrs)*
+                #pat #guard => true,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    quote! {
+        pub fn is_implemented(version: &crate::bigfile::versions::Version, platform: crate::bigfile::platforms::Platform) -> bool {
+            use crate::bigfile::versions::Version::*;
+            use crate::bigfile::platforms::Platform::*;
+            match (version.clone(), platform) {
+                #(#arms)*
+                _ => false,
+            }
+        }
+    }
+}
+
+fn impl_max_block_size(input: &BffBigFileMacroInput) -> proc_macro2::TokenStream {
+    let arms = input
+        .forms
+        .iter()
+        .map(|form| {
+            let attrs = &form.attrs;
+            let pat = &form.pat;
+            let guard = match &form.guard {
+                Some((_, guard)) => quote! { #guard },
+                None => quote! {},
+            };
+            let body = &form.body;
+            quote! {
+                #(#attrs)*
+                #pat #guard => Ok(<#body as BigFileIo>::MAX_BLOCK_SIZE),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    quote! {
+        pub fn max_block_size(version: &crate::bigfile::versions::Version, platform: crate::bigfile::platforms::Platform) -> crate::BffResult<usize> {
+            use crate::bigfile::versions::Version::*;
+            use crate::bigfile::platforms::Platform::*;
+            use crate::traits::BigFileIo;
+            match (version.clone(), platform) {
+                #(#arms)*
+                (version, platform) => Err(crate::error::UnimplementedVersionPlatformError::new(version, platform).into()),
+            }
+        }
+    }
+}
@@ -1,14 +1,14 @@
 use std::collections::HashMap;
 
-use bff::bigfile::BigFile;
 use bff::class::Class;
 use bff::names::Name;
 use bff::traits::TryIntoVersionPlatform;
 
 use crate::artifact::Artifact;
+use crate::helpers::load::GuiBigFile;
 use crate::traits::export::{Export, RecursiveExport};
 
-pub fn create_artifact(bigfile: &BigFile, class: Class) -> Option<Artifact> {
+pub fn create_artifact(bigfile: &GuiBigFile, class: Class) -> Option<Artifact> {
     match class {
         Class::Bitmap(box_bitmap) => {
             let artifact = match *box_bitmap {
@@ -40,14 +40,14 @@ pub fn create_artifact(bigfile: &BigFile, class: Class) -> Option<Artifact> {
                 let dependency_classes: HashMap<Name, Class> = skin
                     .dependencies()
                     .iter()
-                    .filter_map(|n| bigfile.objects.get(n))
+                    .filter_map(|n| bigfile.read_resource(n).ok().flatten())
                     .map(|r| {
                         (
                             r.name,
                             TryIntoVersionPlatform::<Class>::try_into_version_platform(
-                                r,
-                                bigfile.manifest.version.clone(),
-                                bigfile.manifest.platform,
+                                &r,
+                                bigfile.manifest().version.clone(),
+                                bigfile.manifest().platform,
                             )
                             .unwrap(),
                         )
@@ -1,20 +1,82 @@
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 
+use bff::bigfile::lazy::LazyBigFile;
 use bff::bigfile::platforms::Platform;
-use bff::bigfile::BigFile;
+use bff::bigfile::progress::{Progress, ProgressObserver};
+use bff::names::NameContext;
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn load_bf(ctx: egui::Context, path: PathBuf, tx: Sender<Option<(BigFile, PathBuf)>>) {
+pub type BigFileReader = bff::BufReader<std::fs::File>;
+#[cfg(target_arch = "wasm32")]
+pub type BigFileReader = bff::BufReader<std::io::Cursor<Vec<u8>>>;
+
+pub type GuiBigFile = LazyBigFile<BigFileReader>;
+
+// Shared between the loading task and the UI, which shows how many blocks have been read and can
+// cancel the read
+pub struct LoadProgress {
+    ctx: egui::Context,
+    block: AtomicUsize,
+    block_count: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl LoadProgress {
+    pub fn new(ctx: egui::Context) -> Self {
+        Self {
+            ctx,
+            block: AtomicUsize::new(0),
+            block_count: AtomicUsize::new(0),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        let block_count = self.block_count.load(Ordering::Relaxed);
+        match block_count {
+            0 => 0.0,
+            _ => self.block.load(Ordering::Relaxed) as f32 / block_count as f32,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl ProgressObserver for LoadProgress {
+    fn progress(&self, progress: Progress) {
+        if let Progress::Block { index, count } = progress {
+            self.block.store(index, Ordering::Relaxed);
+            self.block_count.store(count, Ordering::Relaxed);
+            self.ctx.request_repaint();
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_bf(
+    ctx: egui::Context,
+    path: PathBuf,
+    name_context: NameContext,
+    tx: Sender<Option<(GuiBigFile, PathBuf)>>,
+) -> Arc<LoadProgress> {
     use std::fs::File;
+    let progress = Arc::new(LoadProgress::new(ctx.clone()));
+    let observer: Arc<dyn ProgressObserver> = progress.clone();
     tokio::spawn(async move {
-        let platform = path
-            .extension()
-            .and_then(|e| e.try_into().ok())
-            .unwrap_or(Platform::PC);
+        let platform: Option<Platform> = path.extension().and_then(|e| e.try_into().ok());
         let f = File::open(&path).unwrap();
-        let mut reader = bff::BufReader::new(f);
-        match BigFile::read_platform(&mut reader, platform) {
+        let reader = bff::BufReader::new(f);
+        match LazyBigFile::read_auto(reader, platform, &name_context, Some(observer)) {
             Ok(bf) => {
                 let _ = tx.send(Some((bf, path)));
             }
@@ -25,6 +87,7 @@ pub fn load_bf(ctx: egui::Context, path: PathBuf, tx: Sender<Option<(BigFile, Pa
         }
         ctx.request_repaint();
     });
+    progress
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -32,16 +95,16 @@ pub fn load_bf(
     ctx: egui::Context,
     file_name: String,
     data: Vec<u8>,
-    tx: Sender<Option<(BigFile, PathBuf)>>,
+    name_context: NameContext,
+    tx: Sender<Option<(GuiBigFile, PathBuf)>>,
 ) {
     use std::ffi::OsStr;
 
-    let platform = file_name
+    let platform: Option<Platform> = file_name
         .rsplit_once(".")
-        .and_then(|e| OsStr::new(e.1).try_into().ok())
-        .unwrap_or(Platform::PC);
-    let mut reader = bff::BufReader::new(std::io::Cursor::new(data));
-    match BigFile::read_platform(&mut reader, platform) {
+        .and_then(|e| OsStr::new(e.1).try_into().ok());
+    let reader = bff::BufReader::new(std::io::Cursor::new(data));
+    match LazyBigFile::read_auto(reader, platform, &name_context, None) {
         Ok(bf) => {
             let _ = tx.send(Some((bf, PathBuf::from(file_name))));
         }
@@ -3,14 +3,15 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
 
 use artifact::Artifact;
-use bff::bigfile::BigFile;
-use bff::names::Name;
+use bff::names::{Name, NameContext};
 #[cfg(not(target_arch = "wasm32"))]
 use clap::Parser;
 #[cfg(not(target_arch = "wasm32"))]
 use helpers::load::load_bf;
+use helpers::load::{GuiBigFile, LoadProgress};
 
 pub mod artifact;
 pub mod helpers;
@@ -205,11 +206,12 @@ enum GuiWindow {
 
 struct Gui {
     open_window: GuiWindow,
-    tx: Sender<Option<(BigFile, PathBuf)>>,
-    rx: Receiver<Option<(BigFile, PathBuf)>>,
-    bigfile: Option<BigFile>,
+    tx: Sender<Option<(GuiBigFile, PathBuf)>>,
+    rx: Receiver<Option<(GuiBigFile, PathBuf)>>,
+    bigfile: Option<GuiBigFile>,
     bigfile_path: Option<PathBuf>,
     bigfile_loading: bool,
+    load_progress: Option<Arc<LoadProgress>>,
     resource_name: Option<Name>,
     nicknames: HashMap<Name, String>,
     nickname_editing: (Name, String),
@@ -227,16 +229,16 @@ impl Gui {
         setup_custom_font(&cc.egui_ctx);
         let (tx, rx) = std::sync::mpsc::channel();
         #[cfg(not(target_arch = "wasm32"))]
-        let bf_loading = match file {
-            Some(path) => {
-                let p = PathBuf::from(path);
-                load_bf(cc.egui_ctx.clone(), p.clone(), tx.clone());
-                true
-            }
-            None => false,
-        };
+        let load_progress = file.map(|path| {
+            load_bf(
+                cc.egui_ctx.clone(),
+                path,
+                NameContext::current(),
+                tx.clone(),
+            )
+        });
         #[cfg(target_arch = "wasm32")]
-        let bf_loading = false;
+        let load_progress = None;
 
         Self {
             open_window: GuiWindow::default(),
@@ -244,7 +246,8 @@ impl Gui {
             rx,
             bigfile: None,
             bigfile_path: None,
-            bigfile_loading: bf_loading,
+            bigfile_loading: load_progress.is_some(),
+            load_progress,
             resource_name: None,
             nicknames: HashMap::new(),
             nickname_editing: (Name::default(), String::new()),
@@ -266,6 +269,7 @@ impl eframe::App for Gui {
                 self.resource_name = None;
             }
             self.bigfile_loading = false;
+            self.load_progress = None;
             ctx.set_cursor_icon(egui::CursorIcon::Default);
         }
 
@@ -273,6 +277,22 @@ impl eframe::App for Gui {
             ctx.set_cursor_icon(egui::CursorIcon::Progress);
         }
 
+        if let Some(progress) = &self.load_progress {
+            egui::TopBottomPanel::bottom("load_progress").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        progress.cancel();
+                    }
+                    ui.add(egui::ProgressBar::new(progress.fraction()).show_percentage());
+                });
+            });
+        }
+
+        let _names = self
+            .bigfile
+            .as_ref()
+            .map(|bigfile| bigfile.index.name_context.enter());
+
         egui::CentralPanel::default()
             .frame(egui::Frame::none().inner_margin(egui::Margin::same(0.0)))
             .show(ctx, |ui| {
@@ -280,6 +300,9 @@ impl eframe::App for Gui {
                 if menubar_reponse.bf_loading {
                     self.bigfile_loading = true;
                 }
+                if let Some(progress) = menubar_reponse.load_progress {
+                    self.load_progress = Some(progress);
+                }
 
                 let resource_list_response = self.resource_list_panel(
                     ui,
@@ -361,7 +384,13 @@ impl eframe::App for Gui {
             ctx.input(|i| {
                 if !i.raw.dropped_files.is_empty() {
                     let path = i.raw.dropped_files.get(0).unwrap().path.as_ref().unwrap();
-                    load_bf(ctx.clone(), path.clone(), self.tx.clone());
+                    self.bigfile_loading = true;
+                    self.load_progress = Some(load_bf(
+                        ctx.clone(),
+                        path.clone(),
+                        NameContext::current(),
+                        self.tx.clone(),
+                    ));
                 }
             });
         }
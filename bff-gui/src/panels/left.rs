@@ -53,8 +53,8 @@ impl Gui {
             .width_range(70.0..=ui.available_width() / 2.0)
             .show_inside(ui, |ui| {
                 if let Some(bigfile) = &self.bigfile {
-                    let version = &bigfile.manifest.version;
-                    let platform = bigfile.manifest.platform;
+                    let version = &bigfile.manifest().version;
+                    let platform = bigfile.manifest().platform;
                     let binding = match ui.memory(|mem| {
                         mem.data
                             .get_temp::<Arc<Mutex<ResourceListState>>>(id_source)
@@ -66,6 +66,7 @@ impl Gui {
                     let mut new_state = state.clone();
                     let mut class_names = new_state.filter.unwrap_or(
                         bigfile
+                            .index
                             .objects
                             .values()
                             .map(|res| res.class_name)
@@ -138,6 +139,7 @@ impl Gui {
                             ui.label(format!(
                                 "{}/{}",
                                 bigfile
+                                    .index
                                     .objects
                                     .values()
                                     .filter(|res| {
@@ -145,7 +147,7 @@ impl Gui {
                                     })
                                     .collect::<Vec<_>>()
                                     .len(),
-                                bigfile.objects.len()
+                                bigfile.index.objects.len()
                             ));
                         });
                     });
@@ -154,9 +156,10 @@ impl Gui {
                     let resources: Arc<Vec<Name>> = if new_state.resources.is_none() || changed_list
                     {
                         let mut res: Vec<(Name, Name, usize)> = bigfile
+                            .index
                             .objects
-                            .values()
-                            .filter(|res| {
+                            .iter()
+                            .filter(|(_, res)| {
                                 *new_state
                                     .filter
                                     .as_ref()
@@ -164,7 +167,7 @@ impl Gui {
                                     .get(&res.class_name)
                                     .unwrap_or(&true)
                             })
-                            .map(|r| (r.name, r.class_name, r.size()))
+                            .map(|(name, r)| (*name, r.class_name, r.size))
                             .collect();
                         match new_state.sort.sort_type {
                             SortType::Name => res.sort_by_cached_key(|k| k.0.to_string()),
@@ -207,8 +210,8 @@ impl Gui {
                                             || self.infos.get(&res).is_none()
                                         {
                                             match bigfile
-                                                .objects
-                                                .get(&res)
+                                                .read_resource(&res)
+                                                .unwrap()
                                                 .unwrap()
                                                 .try_into_version_platform(
                                                     version.clone(),
@@ -243,8 +246,8 @@ impl Gui {
                                             || self.infos.get(&res).is_none()
                                         {
                                             match bigfile
-                                                .objects
-                                                .get(&res)
+                                                .read_resource(&res)
+                                                .unwrap()
                                                 .unwrap()
                                                 .try_into_version_platform(
                                                     version.clone(),
@@ -275,7 +278,7 @@ impl Gui {
                                 let nickname = self.nicknames.get(resource);
                                 let mut tooltip_text = format!(
                                     "Size: {} bytes",
-                                    bigfile.objects.get(resource).unwrap().size()
+                                    bigfile.index.objects.get(resource).unwrap().size
                                 );
                                 if nickname.is_some() {
                                     tooltip_text.push_str(
@@ -290,7 +293,7 @@ impl Gui {
                                                 Some(nn) => nn.to_owned(),
                                                 None => resource.to_string(),
                                             },
-                                            bigfile.objects.get(resource).unwrap().class_name
+                                            bigfile.index.objects.get(resource).unwrap().class_name
                                         ))
                                         .rounding(0.0)
                                         .min_size(egui::vec2(ui.available_width(), 0.0))
@@ -326,8 +329,8 @@ impl Gui {
                                         || self.infos.get(resource).is_none()
                                     {
                                         match bigfile
-                                            .objects
-                                            .get(resource)
+                                            .read_resource(resource)
+                                            .unwrap()
                                             .unwrap()
                                             .try_into_version_platform(version.clone(), platform)
                                         {
@@ -7,18 +7,19 @@ use std::io::Write;
 use std::sync::{Arc, Mutex};
 
 #[cfg(not(target_arch = "wasm32"))]
-use bff::names::Name;
+use bff::names::{Name, NameContext};
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::artifact::Artifact;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::helpers::class::write_class_json;
-use crate::helpers::load::load_bf;
+use crate::helpers::load::{load_bf, LoadProgress};
 use crate::Gui;
 
 #[derive(Default)]
 pub struct MenubarResponse {
     pub bf_loading: bool,
+    pub load_progress: Option<Arc<LoadProgress>>,
 }
 
 impl Gui {
@@ -60,8 +61,9 @@ impl Gui {
 
                                                 if let Ok(f) = File::open(in_name) {
                                                     let mut reader = bff::BufReader::new(f);
-                                                    bff::names::names()
-                                                        .lock()
+                                                    NameContext::current()
+                                                        .names
+                                                        .write()
                                                         .unwrap()
                                                         .read(&mut reader)
                                                         .unwrap();
@@ -70,7 +72,12 @@ impl Gui {
                                         }
                                     }
                                     response.bf_loading = true;
-                                    load_bf(ui.ctx().clone(), path, self.tx.clone());
+                                    response.load_progress = Some(load_bf(
+                                        ui.ctx().clone(),
+                                        path,
+                                        NameContext::current(),
+                                        self.tx.clone(),
+                                    ));
                                 }
                             }
                             #[cfg(target_arch = "wasm32")]
@@ -90,7 +97,13 @@ impl Gui {
                                         .pick_file()
                                         .await
                                         .unwrap();
-                                    load_bf(ctx, dialog.file_name(), dialog.read().await, tx);
+                                    load_bf(
+                                        ctx,
+                                        dialog.file_name(),
+                                        dialog.read().await,
+                                        NameContext::current(),
+                                        tx,
+                                    );
                                 };
                                 async_std::task::block_on(future);
                             }
@@ -127,8 +140,13 @@ impl Gui {
                             for in_name in paths {
                                 let f: File = File::open(in_name).unwrap();
                                 let mut reader = bff::BufReader::new(f);
-                                bff::names::names()
-                                    .lock()
+                                self.bigfile
+                                    .as_ref()
+                                    .unwrap()
+                                    .index
+                                    .name_context
+                                    .names
+                                    .write()
                                     .unwrap()
                                     .read(&mut reader)
                                     .unwrap();
@@ -197,8 +215,8 @@ impl Gui {
                                 .bigfile
                                 .as_ref()
                                 .unwrap()
-                                .objects
-                                .get(&self.resource_name.unwrap())
+                                .read_resource(&self.resource_name.unwrap())
+                                .unwrap()
                                 .unwrap();
                             #[cfg(not(target_arch = "wasm32"))]
                             {
@@ -210,7 +228,8 @@ impl Gui {
                                     self.bigfile
                                         .as_ref()
                                         .unwrap()
-                                        .dump_resource(resource, &mut w)
+                                        .index
+                                        .dump_resource(&resource, &mut w)
                                         .unwrap();
                                 }
                             }
@@ -222,7 +241,8 @@ impl Gui {
                                 self.bigfile
                                     .as_ref()
                                     .unwrap()
-                                    .dump_resource(resource, &mut w)
+                                    .index
+                                    .dump_resource(&resource, &mut w)
                                     .unwrap();
                                 rfd::AsyncFileDialog::new()
                                     .add_filter("raw", &[resource.class_name.to_string()])
@@ -243,7 +263,9 @@ impl Gui {
                         {
                             ui.close_menu();
                             if let Some(directory) = rfd::FileDialog::new().pick_folder() {
-                                for resource in self.bigfile.as_ref().unwrap().objects.values() {
+                                let bigfile = self.bigfile.as_ref().unwrap();
+                                for name in bigfile.index.objects.keys() {
+                                    let resource = bigfile.read_resource(name).unwrap().unwrap();
                                     let name = resource.name;
                                     let class_name = resource.class_name;
                                     let path = directory.join(
@@ -255,11 +277,7 @@ impl Gui {
                                     std::fs::create_dir_all(path.parent().unwrap()).unwrap();
                                     let mut writer =
                                         std::io::BufWriter::new(File::create(path).unwrap());
-                                    self.bigfile
-                                        .as_ref()
-                                        .unwrap()
-                                        .dump_resource(resource, &mut writer)
-                                        .unwrap();
+                                    bigfile.index.dump_resource(&resource, &mut writer).unwrap();
                                 }
                             }
                         }
@@ -330,8 +348,8 @@ impl Gui {
                     ui.menu_button("Info", |ui| {
                         ui.label(format!(
                             "Version: {}\nPlatform: {}",
-                            self.bigfile.as_ref().unwrap().manifest.version,
-                            self.bigfile.as_ref().unwrap().manifest.platform,
+                            self.bigfile.as_ref().unwrap().manifest().version,
+                            self.bigfile.as_ref().unwrap().manifest().platform,
                         ))
                     })
                 });
@@ -4,7 +4,7 @@ use binrw::{args, binread, parser, BinRead, BinResult};
 use xbadpcm::XboxADPCMDecoder;
 
 use crate::bigfile::platforms::Platform;
-use crate::names::{names, Name, NameType};
+use crate::names::{Name, NameContext, NameType};
 
 pub struct Sound {
     pub name: Name,
@@ -66,8 +66,12 @@ pub struct SoundBF {
 }
 
 impl SoundBF {
-    pub fn read_platform<R: Read + Seek>(reader: &mut R, platform: Platform) -> BinResult<Self> {
-        names().lock().unwrap().name_type = NameType::Asobo32;
+    pub fn read_platform<R: Read + Seek>(
+        reader: &mut R,
+        platform: Platform,
+        name_context: &NameContext,
+    ) -> BinResult<Self> {
+        let _names = name_context.with_name_type(NameType::Asobo32).enter();
         Self::read_options(reader, platform.into(), ())
     }
 }
@@ -0,0 +1,198 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{Seek, Write};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bigfile::progress::ProgressObserver;
+use crate::bigfile::resource::Resource;
+use crate::bigfile::BigFile;
+use crate::lz::{Lz4Mode, ZLIB_DEFAULT_LEVEL};
+use crate::names::Name;
+use crate::BffResult;
+
+// Settings of the compressors used when writing a BigFile, the defaults of bff make different
+// streams than the tools of the game. The settings reproducing the streams of a BigFile can be
+// found with `unlz --detect` and set in its manifest to write it back identically.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionParameters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lz4: Option<Lz4Mode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zlib_level: Option<u32>,
+}
+
+impl CompressionParameters {
+    pub fn lz4(parameters: &Option<Self>) -> Lz4Mode {
+        parameters
+            .and_then(|parameters| parameters.lz4)
+            .unwrap_or_default()
+    }
+
+    pub fn zlib_level(parameters: &Option<Self>) -> u32 {
+        parameters
+            .and_then(|parameters| parameters.zlib_level)
+            .unwrap_or(ZLIB_DEFAULT_LEVEL)
+    }
+}
+
+// Decides which objects are compressed when a BigFile is written, objects that match no rule keep
+// their own flag
+#[derive(Debug, Clone, Default)]
+pub struct CompressionPolicy {
+    // Classes that are always or never compressed, such as textures whose data is already
+    // compressed
+    pub classes: HashMap<Name, bool>,
+    // Objects smaller than this are not compressed
+    pub min_size: Option<usize>,
+}
+
+impl CompressionPolicy {
+    pub fn compress(&self, resource: &Resource) -> bool {
+        if let Some(compress) = self.classes.get(&resource.class_name) {
+            return *compress;
+        }
+
+        match self.min_size {
+            Some(min_size) if resource.size() < min_size => false,
+            _ => resource.compress,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, Copy)]
+pub struct CompressionTotals {
+    pub uncompressed_size: u64,
+    // Bytes taken by the object data in the BigFile, the object headers are not counted
+    pub stored_size: u64,
+}
+
+impl CompressionTotals {
+    fn add(&mut self, uncompressed_size: usize, stored_size: usize) {
+        self.uncompressed_size += uncompressed_size as u64;
+        self.stored_size += stored_size as u64;
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ClassCompressionTotals {
+    pub class_name: Name,
+    #[serde(flatten)]
+    pub totals: CompressionTotals,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct CompressionStats {
+    pub blocks: Vec<CompressionTotals>,
+    pub pool: CompressionTotals,
+    // Largest classes first
+    pub classes: Vec<ClassCompressionTotals>,
+}
+
+#[derive(Default)]
+struct CompressionStatsCollector {
+    blocks: Vec<CompressionTotals>,
+    pool: CompressionTotals,
+    classes: HashMap<Name, CompressionTotals>,
+}
+
+thread_local! {
+    static COMPRESSION_STATS: RefCell<Option<CompressionStatsCollector>> = const { RefCell::new(None) };
+}
+
+// Called by the writers for every object they write, objects without a block are in the pool.
+// Nothing is recorded unless the BigFile is written with write_with_stats.
+pub(crate) fn record_object(
+    block_index: Option<usize>,
+    class_name: Name,
+    uncompressed_size: usize,
+    stored_size: usize,
+) {
+    COMPRESSION_STATS.with(|stats| {
+        if let Some(stats) = stats.borrow_mut().as_mut() {
+            let totals = match block_index {
+                Some(block_index) => {
+                    if stats.blocks.len() <= block_index {
+                        stats.blocks.resize(block_index + 1, Default::default());
+                    }
+                    &mut stats.blocks[block_index]
+                }
+                None => &mut stats.pool,
+            };
+            totals.add(uncompressed_size, stored_size);
+            stats
+                .classes
+                .entry(class_name)
+                .or_default()
+                .add(uncompressed_size, stored_size);
+        }
+    });
+}
+
+// Formats that compress whole blocks replace the totals of the block with the sizes of the block
+pub(crate) fn record_block(block_index: usize, uncompressed_size: usize, stored_size: usize) {
+    COMPRESSION_STATS.with(|stats| {
+        if let Some(stats) = stats.borrow_mut().as_mut() {
+            if stats.blocks.len() <= block_index {
+                stats.blocks.resize(block_index + 1, Default::default());
+            }
+            stats.blocks[block_index] = Default::default();
+            stats.blocks[block_index].add(uncompressed_size, stored_size);
+        }
+    });
+}
+
+impl BigFile {
+    // Set the compression flag of every object from the policy
+    pub fn apply_compression_policy(&mut self, policy: &CompressionPolicy) {
+        for resource in self.objects.values_mut() {
+            resource.compress = policy.compress(resource);
+        }
+
+        for object in self
+            .manifest
+            .blocks
+            .iter_mut()
+            .flat_map(|block| block.objects.iter_mut())
+            .filter(|object| object.compress.is_some())
+        {
+            if let Some(resource) = self.objects.get(&object.name) {
+                object.compress = Some(resource.compress);
+            }
+        }
+    }
+
+    // Write the BigFile and total the compressed and uncompressed sizes of its objects per block
+    // and per class
+    pub fn write_with_stats<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        tag: Option<&str>,
+        observer: Option<Arc<dyn ProgressObserver>>,
+    ) -> BffResult<CompressionStats> {
+        let collector = CompressionStatsCollector {
+            blocks: vec![Default::default(); self.manifest.blocks.len()],
+            ..Default::default()
+        };
+        let previous = COMPRESSION_STATS.with(|stats| stats.replace(Some(collector)));
+        let result = self.write(writer, tag, observer);
+        let collector = COMPRESSION_STATS
+            .with(|stats| stats.replace(previous))
+            .unwrap_or_default();
+        result?;
+
+        let mut classes = collector
+            .classes
+            .into_iter()
+            .map(|(class_name, totals)| ClassCompressionTotals { class_name, totals })
+            .collect::<Vec<_>>();
+        classes.sort_by(|a, b| b.totals.uncompressed_size.cmp(&a.totals.uncompressed_size));
+
+        Ok(CompressionStats {
+            blocks: collector.blocks,
+            pool: collector.pool,
+            classes,
+        })
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::bigfile::resource::Resource;
+use crate::bigfile::BigFile;
+use crate::class::Class;
+use crate::names::Name;
+use crate::traits::{ReferencedNames, TryIntoVersionPlatform};
+use crate::BffResult;
+
+#[derive(Serialize, Debug)]
+pub struct DependencyNode {
+    pub name: Name,
+    pub class_name: Name,
+    // Objects of the BigFile referenced by this object in manifest order
+    pub references: Vec<Name>,
+    // Referenced names that are not objects of the BigFile
+    pub missing: Vec<Name>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    // Objects that could not be decoded, they have no outgoing edges
+    pub undecoded: Vec<Name>,
+    #[serde(skip)]
+    indices: HashMap<Name, usize>,
+    #[serde(skip)]
+    referenced_by: HashMap<Name, Vec<Name>>,
+}
+
+impl DependencyGraph {
+    pub fn node(&self, name: &Name) -> Option<&DependencyNode> {
+        self.indices.get(name).map(|i| &self.nodes[*i])
+    }
+
+    pub fn references(&self, name: &Name) -> &[Name] {
+        self.node(name)
+            .map(|node| node.references.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn referenced_by(&self, name: &Name) -> &[Name] {
+        self.referenced_by
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    // Every name referenced but missing from the BigFile with the objects referencing it
+    pub fn missing(&self) -> impl Iterator<Item = (&Name, &[Name])> {
+        self.referenced_by
+            .iter()
+            .filter(|(name, _)| !self.indices.contains_key(name))
+            .map(|(name, referencing)| (name, referencing.as_slice()))
+    }
+
+    pub fn write_dot<W: Write>(&self, writer: &mut W) -> BffResult<()> {
+        writeln!(writer, "digraph bigfile {{")?;
+        for node in self.nodes.iter() {
+            writeln!(
+                writer,
+                "    \"{}\" [label=\"{}\\n{}\"];",
+                escape_dot(&node.name),
+                escape_dot(&node.name),
+                escape_dot(&node.class_name)
+            )?;
+        }
+        for (name, _) in self.missing() {
+            writeln!(
+                writer,
+                "    \"{}\" [style=dashed, color=red];",
+                escape_dot(name)
+            )?;
+        }
+        for node in self.nodes.iter() {
+            for reference in node.references.iter().chain(node.missing.iter()) {
+                writeln!(
+                    writer,
+                    "    \"{}\" -> \"{}\";",
+                    escape_dot(&node.name),
+                    escape_dot(reference)
+                )?;
+            }
+        }
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+}
+
+fn escape_dot(name: &Name) -> String {
+    name.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl BigFile {
+    // The names referenced by the decoded class of a resource, None if it could not be decoded. The
+    // name context must be entered.
+    pub(crate) fn referenced_names(&self, resource: &Resource) -> Option<HashSet<Name>> {
+        let class: Class = resource
+            .try_into_version_platform(self.manifest.version.clone(), self.manifest.platform)
+            .ok()?;
+        Some(class.names())
+    }
+
+    // Build the graph of references between the objects of this BigFile from their decoded classes
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let _names = self.name_context.enter();
+        let null_name = self.name_context.name_type.hash(b"");
+
+        let objects = self
+            .manifest
+            .blocks
+            .iter()
+            .flat_map(|block| block.objects.iter())
+            .filter_map(|object| self.objects.get(&object.name))
+            .collect::<Vec<_>>();
+
+        let mut graph = DependencyGraph {
+            indices: objects
+                .iter()
+                .enumerate()
+                .map(|(i, resource)| (resource.name, i))
+                .collect(),
+            ..Default::default()
+        };
+
+        for resource in objects {
+            let names = match self.referenced_names(resource) {
+                Some(names) => names,
+                None => {
+                    graph.undecoded.push(resource.name);
+                    Default::default()
+                }
+            };
+
+            let (mut references, mut missing): (Vec<Name>, Vec<Name>) = names
+                .into_iter()
+                .filter(|name| {
+                    *name != resource.name && *name != resource.class_name && *name != null_name
+                })
+                .partition(|name| graph.indices.contains_key(name));
+            references.sort_by_key(|name| graph.indices[name]);
+            missing.sort_by_key(Name::to_string);
+
+            for reference in references.iter().chain(missing.iter()) {
+                graph
+                    .referenced_by
+                    .entry(*reference)
+                    .or_default()
+                    .push(resource.name);
+            }
+
+            graph.nodes.push(DependencyNode {
+                name: resource.name,
+                class_name: resource.class_name,
+                references,
+                missing,
+            });
+        }
+
+        graph
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReferencingObject {
+    // Index of the BigFile in the checked set
+    pub bigfile: usize,
+    pub name: Name,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UnresolvedReference {
+    pub name: Name,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub string: Option<String>,
+    pub referenced_by: Vec<ReferencingObject>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UnresolvedClass {
+    pub class_name: Name,
+    pub references: Vec<UnresolvedReference>,
+}
+
+// Resolve the references of every object against the objects of all the BigFiles, the unresolved
+// ones are grouped by the class of the referencing object
+pub fn check_references(bigfiles: &[BigFile]) -> Vec<UnresolvedClass> {
+    let objects = bigfiles
+        .iter()
+        .flat_map(|bigfile| bigfile.objects.keys())
+        .collect::<HashSet<_>>();
+
+    let mut classes: Vec<UnresolvedClass> = Vec::new();
+
+    for (i, bigfile) in bigfiles.iter().enumerate() {
+        let graph = bigfile.dependency_graph();
+        let names = bigfile.name_context.names.read().unwrap();
+
+        for node in graph.nodes.iter() {
+            for name in node.missing.iter().filter(|name| !objects.contains(name)) {
+                let class = match classes
+                    .iter()
+                    .position(|class| class.class_name == node.class_name)
+                {
+                    Some(index) => &mut classes[index],
+                    None => {
+                        classes.push(UnresolvedClass {
+                            class_name: node.class_name,
+                            references: Vec::new(),
+                        });
+                        classes.last_mut().unwrap()
+                    }
+                };
+
+                let reference = match class
+                    .references
+                    .iter()
+                    .position(|reference| reference.name == *name)
+                {
+                    Some(index) => &mut class.references[index],
+                    None => {
+                        class.references.push(UnresolvedReference {
+                            name: *name,
+                            string: names.get(name).map(str::to_owned),
+                            referenced_by: Vec::new(),
+                        });
+                        class.references.last_mut().unwrap()
+                    }
+                };
+
+                reference.referenced_by.push(ReferencingObject {
+                    bigfile: i,
+                    name: node.name,
+                });
+            }
+        }
+    }
+
+    classes
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::bigfile::resource::Resource;
+use crate::bigfile::BigFile;
+use crate::class::Class;
+use crate::names::Name;
+use crate::traits::TryIntoVersionPlatform;
+
+// An object can be listed in more than one block, every block listing it is given
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct MovedObject {
+    pub name: Name,
+    pub from_blocks: Vec<usize>,
+    pub to_blocks: Vec<usize>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FieldChange {
+    // JSON pointer to the field in the decoded class
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChangedObject {
+    pub name: Name,
+    pub class_name: Name,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<(usize, usize)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compress: Option<(bool, bool)>,
+    // None if either side could not be decoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldChange>>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct BigFileDiff {
+    pub added: Vec<Name>,
+    pub removed: Vec<Name>,
+    pub moved: Vec<MovedObject>,
+    pub changed: Vec<ChangedObject>,
+}
+
+impl BigFileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+// Objects in manifest order, once each, with the indices of the blocks listing them
+fn block_indices(bigfile: &BigFile) -> (Vec<Name>, HashMap<Name, Vec<usize>>) {
+    let mut names = Vec::new();
+    let mut blocks: HashMap<Name, Vec<usize>> = HashMap::new();
+
+    for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
+        for object in block.objects.iter() {
+            let indices = blocks.entry(object.name).or_insert_with(|| {
+                names.push(object.name);
+                Vec::new()
+            });
+            if indices.last() != Some(&i) {
+                indices.push(i);
+            }
+        }
+    }
+
+    (names, blocks)
+}
+
+fn class_value(bigfile: &BigFile, resource: &Resource) -> Option<Value> {
+    let _names = bigfile.name_context.enter();
+    let class: Class = resource
+        .try_into_version_platform(bigfile.manifest.version.clone(), bigfile.manifest.platform)
+        .ok()?;
+    serde_json::to_value(class).ok()
+}
+
+fn diff_values(path: String, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            for (key, old_value) in old_fields {
+                let path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match new_fields.get(key) {
+                    Some(new_value) => diff_values(path, old_value, new_value, changes),
+                    None => changes.push(FieldChange {
+                        path,
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new_fields {
+                if !old_fields.contains_key(key) {
+                    changes.push(FieldChange {
+                        path: format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1")),
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items))
+            if old_items.len() == new_items.len() =>
+        {
+            for (i, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                diff_values(format!("{}/{}", path, i), old_item, new_item, changes);
+            }
+        }
+        (old, new) if old != new => changes.push(FieldChange {
+            path,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+impl BigFile {
+    // Compare the objects of this BigFile against a newer one
+    pub fn diff(&self, other: &BigFile) -> BigFileDiff {
+        let mut diff = BigFileDiff::default();
+
+        let (names, blocks) = block_indices(self);
+        let (other_names, other_blocks) = block_indices(other);
+
+        for name in names {
+            let Some(to_blocks) = other_blocks.get(&name) else {
+                diff.removed.push(name);
+                continue;
+            };
+
+            let from_blocks = &blocks[&name];
+            if from_blocks != to_blocks {
+                diff.moved.push(MovedObject {
+                    name,
+                    from_blocks: from_blocks.clone(),
+                    to_blocks: to_blocks.clone(),
+                });
+            }
+
+            // Manifests edited by hand can list objects that do not exist, there is nothing to
+            // compare then
+            let (Some(resource), Some(other_resource)) =
+                (self.objects.get(&name), other.objects.get(&name))
+            else {
+                continue;
+            };
+
+            let size = (resource.size(), other_resource.size());
+            let compress = (resource.compress, other_resource.compress);
+            let data_changed = resource.data != other_resource.data;
+            if !data_changed && compress.0 == compress.1 {
+                continue;
+            }
+
+            let fields = if data_changed {
+                class_value(self, resource)
+                    .zip(class_value(other, other_resource))
+                    .map(|(old, new)| {
+                        let mut changes = Vec::new();
+                        diff_values(String::new(), &old, &new, &mut changes);
+                        changes
+                    })
+            } else {
+                None
+            };
+
+            diff.changed.push(ChangedObject {
+                name,
+                class_name: resource.class_name,
+                size: (size.0 != size.1).then_some(size),
+                compress: (compress.0 != compress.1).then_some(compress),
+                fields,
+            });
+        }
+
+        diff.added = other_names
+            .into_iter()
+            .filter(|name| !blocks.contains_key(name))
+            .collect();
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigfile::testing::{bigfile, block, name, resource};
+
+    #[test]
+    fn removed_only_lists_objects_missing_from_the_other_manifest() {
+        let [a, b] = [name("a"), name("b")];
+        let old = bigfile(
+            vec![block(&[a, b])],
+            vec![resource(a, "Mesh_Z", b"a"), resource(b, "Mesh_Z", b"b")],
+        );
+        // b is still listed even though the other BigFile has no resource for it
+        let new = bigfile(vec![block(&[a, b])], vec![resource(a, "Mesh_Z", b"a")]);
+
+        assert!(old.diff(&new).is_empty());
+
+        let new = bigfile(vec![block(&[a])], vec![resource(a, "Mesh_Z", b"a")]);
+        let diff = old.diff(&new);
+        assert_eq!(diff.removed, vec![b]);
+        assert!(diff.added.is_empty() && diff.moved.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn objects_in_several_blocks_are_listed_once() {
+        let [a, b, c] = [name("a"), name("b"), name("c")];
+        let resources = || {
+            vec![
+                resource(a, "Mesh_Z", b"a"),
+                resource(b, "Mesh_Z", b"b"),
+                resource(c, "Mesh_Z", b"c"),
+            ]
+        };
+        let old = bigfile(vec![block(&[a, b]), block(&[a]), block(&[c])], resources());
+        let mut new = bigfile(vec![block(&[a, b]), block(&[c, a])], resources());
+        new.objects.insert(b, resource(b, "Mesh_Z", b"changed"));
+
+        let diff = old.diff(&new);
+        // a is listed twice in both BigFiles so only c moved
+        assert_eq!(
+            diff.moved,
+            vec![MovedObject {
+                name: c,
+                from_blocks: vec![2],
+                to_blocks: vec![1],
+            }]
+        );
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, b);
+        assert_eq!(diff.changed[0].size, Some((1, 7)));
+    }
+
+    #[test]
+    fn added_objects_are_listed_once() {
+        let [a, b] = [name("a"), name("b")];
+        let old = bigfile(vec![block(&[a])], vec![resource(a, "Mesh_Z", b"a")]);
+        let new = bigfile(
+            vec![block(&[a, b]), block(&[b])],
+            vec![resource(a, "Mesh_Z", b"a"), resource(b, "Mesh_Z", b"b")],
+        );
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![b]);
+        assert!(diff.removed.is_empty());
+    }
+
+    #[test]
+    fn nested_fields_are_diffed_by_pointer() {
+        let old = serde_json::json!({ "a": { "b/c": 1, "d": [1, 2] }, "e": 1 });
+        let new = serde_json::json!({ "a": { "b/c": 2, "d": [1, 3] }, "f": 1 });
+        let mut changes = Vec::new();
+        diff_values(String::new(), &old, &new, &mut changes);
+
+        let paths = changes
+            .iter()
+            .map(|change| change.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["/a/b~1c", "/a/d/1", "/e", "/f"]);
+    }
+}
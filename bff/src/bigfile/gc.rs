@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::bigfile::merge::filter_block;
+use crate::bigfile::BigFile;
+use crate::class::{ClassNameStyle, ClassType};
+use crate::names::{Name, NameType};
+
+#[derive(Serialize, Debug)]
+pub struct ClassSavings {
+    pub class_name: Name,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct OrphanReport {
+    pub roots: Vec<Name>,
+    pub orphans: Vec<Name>,
+    // Objects that could not be decoded are kept since what they reference is unknown
+    pub undecoded: Vec<Name>,
+    pub classes: Vec<ClassSavings>,
+    pub bytes: usize,
+}
+
+// Worlds and GameObjs are loaded by the game directly
+pub(crate) fn is_root_class(class_name: Name) -> bool {
+    let class: Result<(ClassType, ClassNameStyle, NameType), ()> = class_name.try_into();
+    matches!(class, Ok((ClassType::World | ClassType::GameObj, _, _)))
+}
+
+impl BigFile {
+    pub fn default_roots(&self) -> Vec<Name> {
+        self.manifest
+            .blocks
+            .iter()
+            .flat_map(|block| block.objects.iter())
+            .filter_map(|object| self.objects.get(&object.name))
+            .filter(|resource| is_root_class(resource.class_name))
+            .map(|resource| resource.name)
+            .collect()
+    }
+
+    // Find the objects no root can reach. Pooled objects are always kept since the pool refers to
+    // them by index.
+    pub fn find_orphans(&self, roots: &[Name]) -> OrphanReport {
+        let graph = self.dependency_graph();
+
+        let pooled = self
+            .manifest
+            .pool
+            .iter()
+            .flat_map(|pool| pool.object_entries.iter().map(|entry| entry.name))
+            .collect::<HashSet<Name>>();
+
+        let mut reachable = HashSet::new();
+        let mut stack = roots
+            .iter()
+            .chain(graph.undecoded.iter())
+            .chain(pooled.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        while let Some(name) = stack.pop() {
+            if reachable.insert(name) {
+                stack.extend(graph.references(&name).iter().copied());
+            }
+        }
+
+        let mut report = OrphanReport {
+            roots: roots.to_vec(),
+            undecoded: graph.undecoded.clone(),
+            ..Default::default()
+        };
+        let mut classes: HashMap<Name, usize> = HashMap::new();
+
+        for node in graph.nodes.iter() {
+            if reachable.contains(&node.name) {
+                continue;
+            }
+
+            let size = self.objects[&node.name].size();
+            let index = *classes.entry(node.class_name).or_insert_with(|| {
+                report.classes.push(ClassSavings {
+                    class_name: node.class_name,
+                    count: 0,
+                    bytes: 0,
+                });
+                report.classes.len() - 1
+            });
+            report.classes[index].count += 1;
+            report.classes[index].bytes += size;
+            report.bytes += size;
+            report.orphans.push(node.name);
+        }
+
+        report.classes.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        report
+    }
+
+    pub fn strip_orphans(&mut self, report: &OrphanReport) {
+        let orphans = report.orphans.iter().collect::<HashSet<_>>();
+
+        // Blocks without orphans keep their layout
+        self.manifest.blocks = std::mem::take(&mut self.manifest.blocks)
+            .into_iter()
+            .map(|block| {
+                match block
+                    .objects
+                    .iter()
+                    .any(|object| orphans.contains(&object.name))
+                {
+                    true => filter_block(&block, |object| !orphans.contains(&object.name)),
+                    false => block,
+                }
+            })
+            .filter(|block| !block.objects.is_empty())
+            .collect();
+
+        self.objects.retain(|name, _| !orphans.contains(name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigfile::testing::{bigfile, block, name, resource};
+
+    #[test]
+    fn undecoded_objects_are_kept() {
+        let [a, b] = [name("a"), name("b")];
+        let bigfile = bigfile(
+            vec![block(&[a, b])],
+            vec![
+                resource(a, "a.Foo_Z", &[0; 4]),
+                resource(b, "b.Foo_Z", &[0; 4]),
+            ],
+        );
+
+        let report = bigfile.find_orphans(&[]);
+
+        assert_eq!(report.undecoded, vec![a, b]);
+        assert!(report.orphans.is_empty());
+        assert_eq!(report.bytes, 0);
+    }
+
+    #[test]
+    fn stripping_orphans_filters_only_their_blocks() {
+        let [a, b, c, d] = [name("a"), name("b"), name("c"), name("d")];
+        let mut bigfile = bigfile(
+            vec![block(&[a, b]), block(&[c]), block(&[d])],
+            [a, b, c, d]
+                .into_iter()
+                .map(|n| resource(n, "a.Foo_Z", &[0; 4]))
+                .collect(),
+        );
+        bigfile.manifest.blocks[2].offset = Some(0x800);
+
+        bigfile.strip_orphans(&OrphanReport {
+            orphans: vec![b, c],
+            ..Default::default()
+        });
+
+        let blocks = &bigfile.manifest.blocks;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[0]
+                .objects
+                .iter()
+                .map(|object| object.name)
+                .collect::<Vec<_>>(),
+            vec![a]
+        );
+        // The untouched block keeps its layout
+        assert_eq!(blocks[1].offset, Some(0x800));
+        assert_eq!(bigfile.objects.len(), 2);
+        assert!(bigfile.objects.contains_key(&a) && bigfile.objects.contains_key(&d));
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+use crate::bigfile::gc::is_root_class;
+use crate::bigfile::manifest::{ManifestBlock, ManifestObject};
+use crate::bigfile::resource::Resource;
+use crate::bigfile::BigFile;
+use crate::names::Name;
+use crate::BffResult;
+
+// The games allocate their block working buffers from the capacities stored in the header, no
+// version is known to have a hard limit on the size of a block. Planned and merged blocks are kept
+// under this size unless their version sets its own limit.
+pub const DEFAULT_MAX_BLOCK_SIZE: usize = 0x100000;
+
+const SECTOR_SIZE: usize = 2048;
+
+// No version has a larger object header
+const MAX_OBJECT_HEADER_SIZE: usize = 64;
+
+// The working buffer a block needs, counted as if every object was stored uncompressed after its
+// header with room to decompress the largest compressed body in place, padded to a sector
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct BlockSize {
+    objects: usize,
+    working_buffer_offset: usize,
+}
+
+impl BlockSize {
+    pub(crate) fn with(self, resource: &Resource) -> Self {
+        Self {
+            objects: self.objects + MAX_OBJECT_HEADER_SIZE + resource.size(),
+            working_buffer_offset: match resource.compress {
+                true => self.working_buffer_offset.max(resource.size()),
+                false => self.working_buffer_offset,
+            },
+        }
+    }
+
+    pub(crate) fn total(&self) -> usize {
+        self.objects.next_multiple_of(SECTOR_SIZE) + self.working_buffer_offset
+    }
+}
+
+fn new_block(objects: Vec<ManifestObject>) -> ManifestBlock {
+    ManifestBlock {
+        offset: None,
+        checksum: None,
+        compressed: None,
+        objects,
+        resource_counts: None,
+        data: None,
+        unknowns: None,
+    }
+}
+
+impl BigFile {
+    // Replace the blocks of the manifest with a layout of every object. Objects are placed after the
+    // objects they reference, starting from the given roots then Worlds and GameObjs. A block is
+    // closed once the working buffer it needs would exceed the maximum size of the version unless
+    // another one is given, larger objects get a block of their own. The working buffer offsets are
+    // computed when writing.
+    pub fn plan_layout(&mut self, roots: &[Name], max_block_size: Option<usize>) -> BffResult<()> {
+        let max_block_size = match max_block_size {
+            Some(max_block_size) => max_block_size,
+            None => Self::max_block_size(&self.manifest.version, self.manifest.platform)?,
+        };
+        let _names = self.name_context.enter();
+
+        // Sort by name so the layout does not depend on the order the objects were read in
+        let mut names = self.objects.keys().copied().collect::<Vec<_>>();
+        names.sort_by_key(Name::to_string);
+
+        let default_roots = names
+            .iter()
+            .filter(|name| is_root_class(self.objects[name].class_name))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut order = Vec::with_capacity(names.len());
+        let mut visited = HashSet::new();
+
+        for start in roots.iter().chain(default_roots.iter()).chain(names.iter()) {
+            // Post-order depth first search, the flag marks names whose references were pushed
+            let mut stack = vec![(*start, false)];
+            while let Some((name, expanded)) = stack.pop() {
+                if expanded {
+                    order.push(name);
+                    continue;
+                }
+
+                let Some(resource) = self.objects.get(&name) else {
+                    continue;
+                };
+                if !visited.insert(name) {
+                    continue;
+                }
+
+                stack.push((name, true));
+                let mut references = self
+                    .referenced_names(resource)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|reference| !visited.contains(reference))
+                    .collect::<Vec<_>>();
+                references.sort_by_key(|reference| std::cmp::Reverse(reference.to_string()));
+                stack.extend(references.into_iter().map(|reference| (reference, false)));
+            }
+        }
+
+        let mut blocks = Vec::new();
+        let mut objects = Vec::new();
+        let mut block_size = BlockSize::default();
+
+        for name in order {
+            let resource = &self.objects[&name];
+            if !objects.is_empty() && block_size.with(resource).total() > max_block_size {
+                blocks.push(new_block(std::mem::take(&mut objects)));
+                block_size = BlockSize::default();
+            }
+
+            objects.push(ManifestObject {
+                name,
+                compress: Some(resource.compress),
+                pooled: None,
+                external: None,
+                unknowns: None,
+            });
+            block_size = block_size.with(resource);
+        }
+
+        if !objects.is_empty() {
+            blocks.push(new_block(objects));
+        }
+
+        self.manifest.blocks = blocks;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigfile::testing::{bigfile, name, resource};
+
+    fn layout(bigfile: &BigFile) -> Vec<Vec<Name>> {
+        bigfile
+            .manifest
+            .blocks
+            .iter()
+            .map(|block| block.objects.iter().map(|object| object.name).collect())
+            .collect()
+    }
+
+    #[test]
+    fn block_size_counts_headers_padding_and_decompression() {
+        let stored = resource(name("a"), "a.Foo_Z", &[0; 1000]);
+        let compressed = Resource {
+            compress: true,
+            ..stored.clone()
+        };
+
+        assert_eq!(BlockSize::default().with(&stored).total(), 2048);
+        assert_eq!(
+            BlockSize::default().with(&stored).with(&stored).total(),
+            2 * 2048
+        );
+        assert_eq!(BlockSize::default().with(&compressed).total(), 2048 + 1000);
+    }
+
+    #[test]
+    fn blocks_are_closed_at_the_size_limit() {
+        let [a, b, c, d] = [name("a"), name("b"), name("c"), name("d")];
+        let mut bigfile = bigfile(
+            Vec::new(),
+            vec![
+                resource(a, "a.Foo_Z", &[0; 3000]),
+                resource(b, "b.Foo_Z", &[0; 3000]),
+                resource(c, "c.Foo_Z", &[0; 3000]),
+                resource(d, "d.Foo_Z", &[0; 7000]),
+            ],
+        );
+
+        bigfile.plan_layout(&[d, a, b, c], Some(3 * 2048)).unwrap();
+
+        // The object larger than a block gets one of its own
+        assert_eq!(layout(&bigfile), vec![vec![d], vec![a, b], vec![c]]);
+    }
+
+    #[test]
+    fn compressed_objects_leave_room_to_decompress() {
+        let [a, b] = [name("a"), name("b")];
+        let objects = |compress| {
+            [a, b]
+                .into_iter()
+                .map(|n| Resource {
+                    compress,
+                    ..resource(n, "a.Foo_Z", &[0; 2000])
+                })
+                .collect()
+        };
+
+        let mut stored = bigfile(Vec::new(), objects(false));
+        stored.plan_layout(&[a, b], Some(3 * 2048)).unwrap();
+        assert_eq!(layout(&stored), vec![vec![a, b]]);
+
+        let mut compressed = bigfile(Vec::new(), objects(true));
+        compressed.plan_layout(&[a, b], Some(3 * 2048)).unwrap();
+        assert_eq!(layout(&compressed), vec![vec![a], vec![b]]);
+    }
+
+    #[test]
+    fn roots_come_first_then_every_object() {
+        let [a, b, c] = [name("a"), name("b"), name("c")];
+        let mut bigfile = bigfile(
+            Vec::new(),
+            [a, b, c]
+                .into_iter()
+                .map(|n| resource(n, "a.Foo_Z", &[0; 4]))
+                .collect(),
+        );
+
+        bigfile.plan_layout(&[c], None).unwrap();
+
+        let blocks = layout(&bigfile);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0][0], c);
+        assert_eq!(blocks[0].len(), 3);
+        assert!(bigfile.manifest.blocks[0]
+            .objects
+            .iter()
+            .all(|object| object.compress == Some(false)));
+    }
+
+    #[test]
+    fn layout_does_not_depend_on_the_read_order() {
+        let names = ["a", "b", "c", "d", "e"].map(name);
+        let mut first = bigfile(
+            Vec::new(),
+            names
+                .iter()
+                .map(|n| resource(*n, "a.Foo_Z", &[0; 3000]))
+                .collect(),
+        );
+        let mut second = bigfile(
+            Vec::new(),
+            names
+                .iter()
+                .rev()
+                .map(|n| resource(*n, "a.Foo_Z", &[0; 3000]))
+                .collect(),
+        );
+
+        first.plan_layout(&[], Some(3 * 2048)).unwrap();
+        second.plan_layout(&[], Some(3 * 2048)).unwrap();
+
+        assert_eq!(layout(&first), layout(&second));
+    }
+}
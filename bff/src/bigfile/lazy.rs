@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::bigfile::manifest::Manifest;
+use crate::bigfile::platforms::Platform;
+use crate::bigfile::probe::probe;
+use crate::bigfile::progress::ProgressObserver;
+use crate::bigfile::resource::Resource;
+use crate::bigfile::resource::ResourceData::SplitData;
+use crate::bigfile::BigFile;
+use crate::names::{Name, NameContext};
+use crate::BffResult;
+
+#[derive(Debug)]
+pub enum ObjectLocation {
+    // The object can be read from offset, if body_offset is set the body is read from the object
+    // at body_offset instead
+    Stored {
+        offset: u64,
+        body_offset: Option<u64>,
+    },
+    // Formats that cannot be indexed are read eagerly
+    Loaded(Resource),
+}
+
+#[derive(Debug)]
+pub struct IndexedObject {
+    pub class_name: Name,
+    pub size: usize,
+    pub location: ObjectLocation,
+}
+
+impl IndexedObject {
+    pub fn stored(class_name: Name, size: usize, offset: u64) -> Self {
+        Self {
+            class_name,
+            size,
+            location: ObjectLocation::Stored {
+                offset,
+                body_offset: None,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct BigFileIndex {
+    #[serde(flatten)]
+    pub manifest: Manifest,
+    #[serde(skip)]
+    pub objects: HashMap<Name, IndexedObject>,
+    #[serde(skip)]
+    pub name_context: NameContext,
+}
+
+impl From<BigFile> for BigFileIndex {
+    fn from(bigfile: BigFile) -> Self {
+        let objects = bigfile
+            .objects
+            .into_iter()
+            .map(|(name, resource)| {
+                (
+                    name,
+                    IndexedObject {
+                        class_name: resource.class_name,
+                        size: resource.size(),
+                        location: ObjectLocation::Loaded(resource),
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            manifest: bigfile.manifest,
+            objects,
+            name_context: bigfile.name_context,
+        }
+    }
+}
+
+pub struct LazyBigFile<R> {
+    pub index: BigFileIndex,
+    reader: Mutex<R>,
+}
+
+impl<R: Read + Seek> LazyBigFile<R> {
+    pub fn read_platform(
+        mut reader: R,
+        platform: Platform,
+        name_context: &NameContext,
+        observer: Option<Arc<dyn ProgressObserver>>,
+    ) -> BffResult<Self> {
+        let index = BigFileIndex::read_platform(&mut reader, platform, name_context, observer)?;
+        Ok(Self {
+            index,
+            reader: Mutex::new(reader),
+        })
+    }
+
+    pub fn read_auto(
+        mut reader: R,
+        hint: Option<Platform>,
+        name_context: &NameContext,
+        observer: Option<Arc<dyn ProgressObserver>>,
+    ) -> BffResult<Self> {
+        let (_, platform) = probe(&mut reader, hint)?;
+        Self::read_platform(reader, platform, name_context, observer)
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.index.manifest
+    }
+
+    pub fn read_resource(&self, name: &Name) -> BffResult<Option<Resource>> {
+        let Some(object) = self.index.objects.get(name) else {
+            return Ok(None);
+        };
+
+        match &object.location {
+            ObjectLocation::Loaded(resource) => Ok(Some(resource.clone())),
+            ObjectLocation::Stored {
+                offset,
+                body_offset,
+            } => {
+                let mut reader = self.reader.lock().unwrap();
+                reader.seek(SeekFrom::Start(*offset))?;
+                let mut resource = self.index.read_resource(&mut *reader)?;
+
+                if let Some(body_offset) = body_offset {
+                    reader.seek(SeekFrom::Start(*body_offset))?;
+                    let body_resource = self.index.read_resource(&mut *reader)?;
+                    if let (
+                        SplitData { body, .. },
+                        SplitData {
+                            body: stored_body, ..
+                        },
+                    ) = (&mut resource.data, body_resource.data)
+                    {
+                        *body = stored_body;
+                        resource.compress = body_resource.compress;
+                    }
+                }
+
+                Ok(Some(resource))
+            }
+        }
+    }
+
+    pub fn into_bigfile(self) -> BffResult<BigFile> {
+        let mut objects = HashMap::with_capacity(self.index.objects.len());
+
+        for name in self.index.objects.keys() {
+            if let Some(resource) = self.read_resource(name)? {
+                objects.insert(*name, resource);
+            }
+        }
+
+        Ok(BigFile {
+            manifest: self.index.manifest,
+            objects,
+            name_context: self.index.name_context,
+        })
+    }
+}
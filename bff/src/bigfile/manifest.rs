@@ -1,44 +1,80 @@
 use serde::{Deserialize, Serialize};
 
+use crate::bigfile::compression::CompressionParameters;
 use crate::bigfile::platforms::Platform;
 use crate::bigfile::versions::{Version, VersionXple};
 use crate::names::Name;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct ManifestPoolObjectEntry {
     pub name: Name,
     pub reference_record_index: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct ManifestPoolReferenceRecord {
     pub object_entries_starting_index: u32,
     pub object_entries_count: u16,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct ManifestPool {
     pub object_entry_indices: Vec<u32>,
     pub object_entries: Vec<ManifestPoolObjectEntry>,
     pub reference_records: Vec<ManifestPoolReferenceRecord>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// A resource entry in a level BigFile whose data is stored in the companion Common BigFile
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ManifestExternalObject {
+    pub class_name: Name,
+    pub offset: u32,
+    pub compressed_size: u32,
+    pub decompressed_size: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ManifestObject {
     pub name: Name,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub compress: Option<bool>,
+    // The body is stored in the pool, the pool manifest is generated from this flag when it is not
+    // given and must agree with it when it is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pooled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external: Option<ManifestExternalObject>,
+    // Unknown fields of the resource entry of the object, kept so the BigFile is written back
+    // identically
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknowns: Option<[u32; 2]>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ManifestBlockData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    pub object_count: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ManifestBlock {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub offset: Option<u64>,
+    // Informational, the versions that store a checksum compute it again when writing
     #[serde(skip_serializing_if = "Option::is_none")]
     pub checksum: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub compressed: Option<bool>,
     pub objects: Vec<ManifestObject>,
+    // Objects are stored in the order: resource groups, then data chunks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_counts: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<ManifestBlockData>>,
+    // Unknown fields of the block description then of the resources map
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknowns: Option<[u64; 8]>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
@@ -64,4 +100,8 @@ pub struct Manifest {
     pub blocks: Vec<ManifestBlock>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pool: Option<ManifestPool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header_unknowns: Option<[u64; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionParameters>,
 }
@@ -0,0 +1,337 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::bigfile::layout::BlockSize;
+use crate::bigfile::manifest::{ManifestBlock, ManifestBlockData, ManifestObject};
+use crate::bigfile::BigFile;
+use crate::error::{MergeConflictError, MergeMismatchError};
+use crate::names::Name;
+use crate::BffResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    FirstWins,
+    LastWins,
+    Error,
+}
+
+// Keep the objects matching the filter, shrinking the resource groups and data chunks they belong
+// to so the block layout stays valid
+pub(crate) fn filter_block(
+    block: &ManifestBlock,
+    keep: impl Fn(&ManifestObject) -> bool,
+) -> ManifestBlock {
+    let resource_count = block.resource_counts.as_ref().map_or(0, Vec::len);
+    let group_sizes = block
+        .resource_counts
+        .iter()
+        .flatten()
+        .copied()
+        .chain(block.data.iter().flatten().map(|data| data.object_count))
+        .collect::<Vec<_>>();
+
+    let mut kept_counts = vec![0u32; group_sizes.len()];
+    let mut objects = Vec::new();
+    let mut group = 0;
+    let mut group_remaining = group_sizes.first().copied().unwrap_or(0);
+
+    for object in block.objects.iter() {
+        while group < group_sizes.len() && group_remaining == 0 {
+            group += 1;
+            group_remaining = group_sizes.get(group).copied().unwrap_or(0);
+        }
+        group_remaining = group_remaining.saturating_sub(1);
+
+        if keep(object) {
+            if let Some(count) = kept_counts.get_mut(group) {
+                *count += 1;
+            }
+            objects.push(object.clone());
+        }
+    }
+
+    ManifestBlock {
+        offset: None,
+        checksum: None,
+        compressed: block.compressed,
+        objects,
+        resource_counts: block
+            .resource_counts
+            .as_ref()
+            .map(|_| kept_counts[..resource_count].to_vec()),
+        data: block.data.as_ref().map(|_| {
+            kept_counts[resource_count..]
+                .iter()
+                .map(|object_count| ManifestBlockData {
+                    offset: None,
+                    object_count: *object_count,
+                })
+                .collect()
+        }),
+        unknowns: block.unknowns,
+    }
+}
+
+impl BigFile {
+    // Put the objects of consecutive blocks together while the working buffer they need fits the
+    // maximum size, filtering leaves many small blocks behind. Blocks with resource groups or data
+    // chunks keep their layout.
+    fn pack_blocks(&self, blocks: Vec<ManifestBlock>, max_block_size: usize) -> Vec<ManifestBlock> {
+        let size = |block_size: BlockSize, objects: &[&ManifestObject]| {
+            objects
+                .iter()
+                .filter_map(|object| self.objects.get(&object.name))
+                .fold(block_size, BlockSize::with)
+        };
+        let grouped =
+            |block: &ManifestBlock| block.resource_counts.is_some() || block.data.is_some();
+
+        let mut packed: Vec<ManifestBlock> = Vec::new();
+        let mut packed_size = BlockSize::default();
+
+        for block in blocks {
+            match packed.last_mut() {
+                Some(last)
+                    if !grouped(&*last)
+                        && !grouped(&block)
+                        && last.compressed == block.compressed
+                        && last.unknowns == block.unknowns =>
+                {
+                    // An object listed by both blocks is only needed once
+                    let objects = block
+                        .objects
+                        .iter()
+                        .filter(|object| !last.objects.iter().any(|o| o.name == object.name))
+                        .collect::<Vec<_>>();
+                    let combined_size = size(packed_size, &objects);
+                    if combined_size.total() <= max_block_size {
+                        last.objects.extend(objects.into_iter().cloned());
+                        packed_size = combined_size;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+
+            packed_size = size(
+                BlockSize::default(),
+                &block.objects.iter().collect::<Vec<_>>(),
+            );
+            packed.push(block);
+        }
+
+        packed
+    }
+
+    // Add the objects of another BigFile to this one. Replaced objects stay in their block, new
+    // objects keep the order they had in the other BigFile, in as few blocks as fit the block size
+    // limit of the version. Objects that were pooled in the other BigFile are stored in their
+    // block.
+    pub fn merge(&mut self, other: BigFile, policy: ConflictPolicy) -> BffResult<()> {
+        let version = self.manifest.version.to_string();
+        let other_version = other.manifest.version.to_string();
+        if version != other_version {
+            return Err(MergeMismatchError::new("version", version, other_version).into());
+        }
+        if self.manifest.platform != other.manifest.platform {
+            return Err(MergeMismatchError::new(
+                "platform",
+                self.manifest.platform.to_string(),
+                other.manifest.platform.to_string(),
+            )
+            .into());
+        }
+        // Both BigFiles hash names the same way since their versions match, the strings known for
+        // the objects of the other one are kept
+        if !Arc::ptr_eq(&self.name_context.names, &other.name_context.names) {
+            let other_names = other.name_context.names.read().unwrap();
+            self.name_context
+                .names
+                .write()
+                .unwrap()
+                .extend(&other_names);
+        }
+
+        let conflicts = other
+            .objects
+            .iter()
+            .filter(|(name, resource)| {
+                self.objects
+                    .get(name)
+                    .is_some_and(|existing| existing != *resource)
+            })
+            .map(|(name, _)| *name)
+            .collect::<HashSet<Name>>();
+
+        if policy == ConflictPolicy::Error && !conflicts.is_empty() {
+            return Err(MergeConflictError::new(conflicts.into_iter().collect()).into());
+        }
+
+        let existing = self
+            .manifest
+            .blocks
+            .iter()
+            .flat_map(|block| block.objects.iter().map(|object| object.name))
+            .collect::<HashSet<Name>>();
+
+        if policy == ConflictPolicy::LastWins {
+            for block in self.manifest.blocks.iter_mut() {
+                if block
+                    .objects
+                    .iter()
+                    .any(|object| conflicts.contains(&object.name))
+                {
+                    // The replaced objects change the size of the block, the writer computes its
+                    // working buffer offsets again. The versions that store a checksum compute it
+                    // when writing.
+                    block.offset = None;
+                    block.checksum = None;
+                    for data in block.data.iter_mut().flatten() {
+                        data.offset = None;
+                    }
+                }
+            }
+        }
+
+        let blocks = other
+            .manifest
+            .blocks
+            .iter()
+            .map(|block| {
+                let mut block = filter_block(block, |object| !existing.contains(&object.name));
+                // The pool of the other BigFile is not merged
+                for object in block.objects.iter_mut() {
+                    object.pooled = None;
+                }
+                block
+            })
+            .filter(|block| !block.objects.is_empty())
+            .collect::<Vec<_>>();
+
+        for (name, resource) in other.objects.into_iter() {
+            if !self.objects.contains_key(&name)
+                || (policy == ConflictPolicy::LastWins && conflicts.contains(&name))
+            {
+                self.objects.insert(name, resource);
+            }
+        }
+
+        let max_block_size = Self::max_block_size(&self.manifest.version, self.manifest.platform)?;
+        let blocks = self.pack_blocks(blocks, max_block_size);
+        self.manifest.blocks.extend(blocks);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigfile::layout::DEFAULT_MAX_BLOCK_SIZE;
+    use crate::bigfile::testing::{bigfile, block, name, resource};
+    use crate::bigfile::v1_06_63_02_pc::pool::{check_pooled_flags, generate_manifest_pool};
+
+    #[test]
+    fn new_objects_are_packed_under_the_block_size_limit() {
+        let [a, b, c, d] = [name("a"), name("b"), name("c"), name("d")];
+        let mut base = bigfile(vec![block(&[a])], vec![resource(a, "Mesh_Z", b"a")]);
+        let large = vec![0; DEFAULT_MAX_BLOCK_SIZE];
+        let other = bigfile(
+            vec![block(&[a, b]), block(&[b, c]), block(&[d])],
+            vec![
+                resource(a, "Mesh_Z", b"a"),
+                resource(b, "Mesh_Z", b"b"),
+                resource(c, "Mesh_Z", b"c"),
+                resource(d, "Mesh_Z", &large),
+            ],
+        );
+
+        base.merge(other, ConflictPolicy::Error).unwrap();
+
+        let blocks = base
+            .manifest
+            .blocks
+            .iter()
+            .map(|block| {
+                block
+                    .objects
+                    .iter()
+                    .map(|object| object.name)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![vec![a], vec![b, c], vec![d]]);
+        assert_eq!(base.objects.len(), 4);
+    }
+
+    #[test]
+    fn conflicts_follow_the_policy() {
+        let a = name("a");
+        let base = || bigfile(vec![block(&[a])], vec![resource(a, "Mesh_Z", b"old")]);
+        let other = || bigfile(vec![block(&[a])], vec![resource(a, "Mesh_Z", b"new")]);
+
+        let mut merged = base();
+        assert!(merged.merge(other(), ConflictPolicy::Error).is_err());
+
+        let mut merged = base();
+        merged.merge(other(), ConflictPolicy::FirstWins).unwrap();
+        assert_eq!(merged.objects[&a], resource(a, "Mesh_Z", b"old"));
+
+        let mut merged = base();
+        merged.merge(other(), ConflictPolicy::LastWins).unwrap();
+        assert_eq!(merged.objects[&a], resource(a, "Mesh_Z", b"new"));
+        assert_eq!(merged.manifest.blocks.len(), 1);
+    }
+
+    #[test]
+    fn pooled_objects_are_stored_in_their_block() {
+        let [a, b, c] = [name("a"), name("b"), name("c")];
+        let mut base = bigfile(vec![block(&[a])], vec![resource(a, "Mesh_Z", b"a")]);
+        base.manifest.blocks[0].objects[0].pooled = Some(true);
+        base.manifest.pool = generate_manifest_pool(&base.manifest.blocks);
+
+        let mut other = bigfile(
+            vec![block(&[b, c])],
+            vec![resource(b, "Mesh_Z", b"b"), resource(c, "Mesh_Z", b"c")],
+        );
+        other.manifest.blocks[0].objects[0].pooled = Some(true);
+        other.manifest.pool = generate_manifest_pool(&other.manifest.blocks);
+
+        base.merge(other, ConflictPolicy::Error).unwrap();
+
+        let merged = &base.manifest.blocks[1];
+        assert!(merged.objects.iter().all(|object| object.pooled.is_none()));
+        let pool = base.manifest.pool.as_ref().unwrap();
+        assert!(check_pooled_flags(&base.manifest.blocks, pool).is_ok());
+        assert_eq!(
+            generate_manifest_pool(&base.manifest.blocks).as_ref(),
+            Some(pool)
+        );
+    }
+
+    #[test]
+    fn filter_block_shrinks_groups() {
+        let [a, b, c, d] = [name("a"), name("b"), name("c"), name("d")];
+        let mut grouped = block(&[a, b, c, d]);
+        grouped.resource_counts = Some(vec![2, 1]);
+        grouped.data = Some(vec![ManifestBlockData {
+            offset: Some(0x800),
+            object_count: 1,
+        }]);
+
+        let filtered = filter_block(&grouped, |object| object.name != b && object.name != d);
+
+        assert_eq!(filtered.resource_counts, Some(vec![1, 1]));
+        let data = filtered.data.unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!((data[0].offset, data[0].object_count), (None, 0));
+        assert_eq!(
+            filtered
+                .objects
+                .iter()
+                .map(|object| object.name)
+                .collect::<Vec<_>>(),
+            vec![a, c]
+        );
+    }
+}
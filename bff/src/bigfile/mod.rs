@@ -1,6 +1,18 @@
+pub mod compression;
+pub mod deps;
+pub mod diff;
+pub mod gc;
+pub mod layout;
+pub mod lazy;
 pub mod manifest;
+pub mod merge;
 pub mod platforms;
+pub mod probe;
+pub mod progress;
 pub mod resource;
+pub mod salvage;
+#[cfg(test)]
+mod testing;
 mod v1_06_63_02_pc;
 mod v1_08_40_02_pc;
 mod v1_2000_77_18_pc;
@@ -11,14 +23,19 @@ mod v2_0_pc;
 mod v2_128_52_19_pc;
 mod v2_128_92_19_pc;
 mod v2_256_38_19_pc;
+pub mod verify;
 pub mod versions;
 
 use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::sync::Arc;
 
 use bff_derive::bigfiles;
 use serde::Serialize;
 
-use crate::bigfile::manifest::Manifest;
+use crate::bigfile::manifest::{BigFileType, Manifest};
+use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::ProgressObserver;
 use crate::bigfile::resource::Resource;
 use crate::bigfile::v1_06_63_02_pc::BigFileV1_06_63_02PC;
 use crate::bigfile::v1_08_40_02_pc::BigFileV1_08_40_02PC;
@@ -34,7 +51,16 @@ use crate::bigfile::v2_0_pc::BigFileV2_0PC;
 use crate::bigfile::v2_128_52_19_pc::BigFileV2_128_52_19PC;
 use crate::bigfile::v2_128_92_19_pc::BigFileV2_128_92_19PC;
 use crate::bigfile::v2_256_38_19_pc::BigFileV2_256_38_19PC;
-use crate::names::Name;
+use crate::class::Class;
+use crate::error::{
+    NotCommonBigFileError,
+    ObjectNotFoundError,
+    RetargetObjectError,
+    UnresolvedExternalObjectsError,
+};
+use crate::names::{Name, NameContext};
+use crate::traits::TryIntoVersionPlatform;
+use crate::BffResult;
 
 pub static DEFAULT_TAG: &str = "made with <3 by bff contributors (https://github.com/widberg/bff)";
 
@@ -44,6 +70,8 @@ pub struct BigFile {
     pub manifest: Manifest,
     #[serde(skip)]
     pub objects: HashMap<Name, Resource>,
+    #[serde(skip)]
+    pub name_context: NameContext,
 }
 
 bigfiles! {
@@ -61,3 +89,94 @@ bigfiles! {
     (Asobo(2, 256, _, _), _) => BigFileV2_256_38_19PC,
     (Asobo(2, 128, 52, _), _) => BigFileV2_128_52_19PC,
 }
+
+impl BigFile {
+    // The object of a manifest entry, manifests edited by hand can list objects that do not exist
+    pub fn object(&self, name: &Name) -> Result<&Resource, ObjectNotFoundError> {
+        self.objects
+            .get(name)
+            .ok_or_else(|| ObjectNotFoundError::new(*name))
+    }
+
+    // Read a BigFile whose platform is not known, the hint is used if its endianness matches
+    pub fn read_auto<R: Read + Seek>(
+        reader: &mut R,
+        hint: Option<Platform>,
+        name_context: &NameContext,
+        observer: Option<Arc<dyn ProgressObserver>>,
+    ) -> BffResult<Self> {
+        let (_, platform) = probe::probe(reader, hint)?;
+        Self::read_platform(reader, platform, name_context, observer)
+    }
+
+    // Re-encode every object for another platform. The resource headers are written with the
+    // target endianness when the BigFile is written.
+    pub fn retarget(mut self, platform: Platform) -> BffResult<Self> {
+        let version = &self.manifest.version;
+        let _names = self.name_context.enter();
+
+        for (name, resource) in self.objects.iter_mut() {
+            let retargeted: BffResult<Resource> = (&*resource)
+                .try_into_version_platform(version.clone(), self.manifest.platform)
+                .and_then(|class: Class| {
+                    (&class).try_into_version_platform(version.clone(), platform)
+                });
+            let retargeted = retargeted.map_err(|err| {
+                RetargetObjectError::new(*name, resource.class_name, platform, Box::new(err))
+            })?;
+            *resource = Resource {
+                compress: resource.compress,
+                ..retargeted
+            };
+        }
+
+        self.manifest.platform = platform;
+        Ok(self)
+    }
+
+    // Both BigFiles are read with the same name context and progress observer
+    pub fn read_platform_with_common<R: Read + Seek, C: Read + Seek>(
+        reader: &mut R,
+        common_reader: &mut C,
+        platform: Platform,
+        name_context: &NameContext,
+        observer: Option<Arc<dyn ProgressObserver>>,
+    ) -> BffResult<Self> {
+        let common = Self::read_platform(common_reader, platform, name_context, observer.clone())?;
+        let mut bigfile = Self::read_platform(reader, platform, name_context, observer)?;
+        bigfile.resolve_common(&common)?;
+        Ok(bigfile)
+    }
+
+    // Copy the objects referenced by this BigFile out of its Common BigFile. The manifest keeps
+    // the references so writing the BigFile emits references rather than data.
+    pub fn resolve_common(&mut self, common: &BigFile) -> BffResult<()> {
+        if !matches!(common.manifest.bigfile_type, Some(BigFileType::Common)) {
+            return Err(NotCommonBigFileError::new(common.manifest.bigfile_type).into());
+        }
+
+        let mut unresolved = Vec::new();
+
+        for object in self
+            .manifest
+            .blocks
+            .iter_mut()
+            .flat_map(|block| block.objects.iter_mut())
+            .filter(|object| object.external.is_some())
+        {
+            match common.objects.get(&object.name) {
+                Some(resource) => {
+                    object.compress = Some(resource.compress);
+                    self.objects.insert(object.name, resource.clone());
+                }
+                None => unresolved.push(object.name),
+            }
+        }
+
+        if unresolved.is_empty() {
+            Ok(())
+        } else {
+            Err(UnresolvedExternalObjectsError::new(unresolved).into())
+        }
+    }
+}
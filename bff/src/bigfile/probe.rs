@@ -0,0 +1,181 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use binrw::{BinRead, Endian};
+
+use crate::bigfile::platforms::Platform;
+use crate::bigfile::versions::Version;
+use crate::bigfile::BigFile;
+use crate::error::UnimplementedVersionPlatformError;
+use crate::helpers::FixedStringNull;
+use crate::BffResult;
+
+const PROBED_WORD_COUNT: usize = 16;
+
+// Every format starts with the version string followed by header fields which are mostly small
+// counts, sizes and offsets. Read with the wrong endianness their low bytes end up on top, so the
+// endianness in which the non-zero fields have the most leading zeros is the right one. Zero
+// fields read the same either way and don't count.
+fn magnitude_score(words: &[[u8; 4]], endian: Endian) -> u32 {
+    words
+        .iter()
+        .map(|bytes| match endian {
+            Endian::Big => u32::from_be_bytes(*bytes),
+            Endian::Little => u32::from_le_bytes(*bytes),
+        })
+        .filter(|word| *word != 0)
+        .map(u32::leading_zeros)
+        .sum()
+}
+
+// The platform most BigFiles of the version with this endianness come from. Little endian
+// BigFiles are read the same on every little endian platform so PC is used, big endian ones are
+// attributed to the console the engine of the version shipped on.
+fn plausible_platform(version: &Version, endian: Endian) -> Platform {
+    match (endian, version) {
+        (Endian::Little, _) => Platform::PC,
+        (
+            Endian::Big,
+            Version::Kalisto(..)
+            | Version::BlackSheep(..)
+            | Version::AsoboLegacy(..)
+            | Version::Ubisoft { .. },
+        ) => Platform::GameCube,
+        (Endian::Big, Version::Asobo(1, ..)) => Platform::Wii,
+        (Endian::Big, _) => Platform::Xbox360,
+    }
+}
+
+// Probe the header in both endiannesses and return the version and the most plausible platform.
+// The hint, usually taken from the file extension, is used when it has the detected endianness
+// and breaks the tie when both endiannesses score the same. The reader is left where it started.
+pub fn probe<R: Read + Seek>(
+    reader: &mut R,
+    hint: Option<Platform>,
+) -> BffResult<(Version, Platform)> {
+    let start = reader.stream_position()?;
+
+    let version: Version = FixedStringNull::<256>::read_be(reader)?.as_str().into();
+
+    let mut words = Vec::with_capacity(PROBED_WORD_COUNT);
+    let mut bytes = [0u8; 4];
+    while words.len() < PROBED_WORD_COUNT && reader.read_exact(&mut bytes).is_ok() {
+        words.push(bytes);
+    }
+    reader.seek(SeekFrom::Start(start))?;
+
+    let hint_endian = hint.map(Endian::from);
+    let little = magnitude_score(&words, Endian::Little);
+    let big = magnitude_score(&words, Endian::Big);
+    let endian = match little.cmp(&big) {
+        std::cmp::Ordering::Greater => Endian::Little,
+        std::cmp::Ordering::Less => Endian::Big,
+        std::cmp::Ordering::Equal => hint_endian.unwrap_or(Endian::Little),
+    };
+
+    let platform = match hint {
+        Some(hint) if hint_endian == Some(endian) => hint,
+        _ => plausible_platform(&version, endian),
+    };
+
+    if !BigFile::is_implemented(&version, platform) {
+        return Err(UnimplementedVersionPlatformError::new(version, platform).into());
+    }
+
+    Ok((version, platform))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::BffError;
+
+    const VERSION: &str = "v1.06.63.02 - Asobo Studio - Internal Cross Technology";
+
+    // Block count, working buffer capacities, padded size, names and resource offsets
+    const WORDS: [u32; 8] = [3, 0x40000, 0x20000, 0x180800, 0, 0x20, 1200, 0x5c];
+
+    fn header(version: &str, endian: Endian) -> Cursor<Vec<u8>> {
+        let mut data = version.as_bytes().to_vec();
+        data.resize(256, 0);
+        for word in WORDS {
+            data.extend(match endian {
+                Endian::Big => word.to_be_bytes(),
+                Endian::Little => word.to_le_bytes(),
+            });
+        }
+        Cursor::new(data)
+    }
+
+    #[test]
+    fn magnitude_score_prefers_the_written_endianness() {
+        let words = WORDS.map(u32::to_le_bytes);
+        assert!(magnitude_score(&words, Endian::Little) > magnitude_score(&words, Endian::Big));
+        let words = WORDS.map(u32::to_be_bytes);
+        assert!(magnitude_score(&words, Endian::Big) > magnitude_score(&words, Endian::Little));
+    }
+
+    #[test]
+    fn little_endian_header_uses_the_hint() {
+        let mut reader = header(VERSION, Endian::Little);
+        let (version, platform) = probe(&mut reader, Some(Platform::PSP)).unwrap();
+        assert!(matches!(version, Version::Asobo(1, 6, 63, 2)));
+        assert_eq!(platform, Platform::PSP);
+        assert_eq!(reader.position(), 0);
+    }
+
+    #[test]
+    fn big_endian_header_uses_the_hint() {
+        let mut reader = header(VERSION, Endian::Big);
+        let (_, platform) = probe(&mut reader, Some(Platform::Xbox360)).unwrap();
+        assert_eq!(platform, Platform::Xbox360);
+    }
+
+    #[test]
+    fn mismatched_hint_is_replaced() {
+        let mut reader = header(VERSION, Endian::Big);
+        let (_, platform) = probe(&mut reader, Some(Platform::PC)).unwrap();
+        assert_eq!(platform, Platform::Wii);
+
+        let mut reader = header(VERSION, Endian::Little);
+        let (_, platform) = probe(&mut reader, Some(Platform::Xbox360)).unwrap();
+        assert_eq!(platform, Platform::PC);
+    }
+
+    #[test]
+    fn missing_hint_uses_the_endianness() {
+        let mut reader = header(VERSION, Endian::Little);
+        let (_, platform) = probe(&mut reader, None).unwrap();
+        assert_eq!(platform, Platform::PC);
+
+        let mut reader = header(VERSION, Endian::Big);
+        let (_, platform) = probe(&mut reader, None).unwrap();
+        assert_eq!(platform, Platform::Wii);
+
+        let version = "v2.128.92.19 - Asobo Studio - Internal Cross Technology";
+        let mut reader = header(version, Endian::Big);
+        let (_, platform) = probe(&mut reader, None).unwrap();
+        assert_eq!(platform, Platform::Xbox360);
+    }
+
+    #[test]
+    fn hint_breaks_ties() {
+        let mut data = VERSION.as_bytes().to_vec();
+        data.resize(256 + 16, 0);
+
+        let (_, platform) = probe(&mut Cursor::new(data.clone()), Some(Platform::PS3)).unwrap();
+        assert_eq!(platform, Platform::PS3);
+        let (_, platform) = probe(&mut Cursor::new(data), None).unwrap();
+        assert_eq!(platform, Platform::PC);
+    }
+
+    #[test]
+    fn unimplemented_version_is_rejected() {
+        let mut reader = header("Not a BigFile", Endian::Little);
+        assert!(matches!(
+            probe(&mut reader, Some(Platform::PC)),
+            Err(BffError::UnimplementedVersionPlatform(_))
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::error::CancelledError;
+use crate::names::Name;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Progress {
+    // A block is being read or written, count is the number of blocks in the BigFile
+    Block { index: usize, count: usize },
+    // An object has been read or written
+    Object { name: Name },
+}
+
+// Receives the progress of the reads and writes it is passed to, including the work they hand to
+// the worker threads. Readers and writers check for cancellation with every event and fail with a
+// CancelledError once it is requested.
+pub trait ProgressObserver: Send + Sync {
+    fn progress(&self, progress: Progress);
+
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+thread_local! {
+    static PROGRESS_OBSERVER: RefCell<Option<Arc<dyn ProgressObserver>>> = const { RefCell::new(None) };
+}
+
+// The observer of the read or write running on this thread
+pub(crate) fn current() -> Option<Arc<dyn ProgressObserver>> {
+    PROGRESS_OBSERVER.with(|current| current.borrow().clone())
+}
+
+// Make the observer the current thread's observer until the guard is dropped
+pub(crate) fn observe(observer: Option<Arc<dyn ProgressObserver>>) -> ProgressObserverGuard {
+    let previous = PROGRESS_OBSERVER.with(|current| current.replace(observer));
+    ProgressObserverGuard {
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+pub(crate) struct ProgressObserverGuard {
+    previous: Option<Arc<dyn ProgressObserver>>,
+    // The guard restores the observer of the thread it was created on
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ProgressObserverGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        PROGRESS_OBSERVER.with(|current| current.replace(previous));
+    }
+}
+
+pub(crate) fn report(progress: Progress) -> Result<(), CancelledError> {
+    // The observer is cloned out so it may read or write on this thread itself
+    match current() {
+        Some(observer) => {
+            observer.progress(progress);
+            match observer.cancelled() {
+                true => Err(CancelledError),
+                false => Ok(()),
+            }
+        }
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn report_objects(names: impl IntoIterator<Item = Name>) -> Result<(), CancelledError> {
+    names
+        .into_iter()
+        .try_for_each(|name| report(Progress::Object { name }))
+}
+
+// Parsers report the cancellation at their position
+pub(crate) fn cancelled_at(pos: u64) -> impl FnOnce(CancelledError) -> binrw::Error {
+    move |err| binrw::Error::Custom {
+        pos,
+        err: Box::new(err),
+    }
+}
@@ -1,12 +1,12 @@
 use crate::names::Name;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum ResourceData {
     Data(Vec<u8>),
     SplitData { link_header: Vec<u8>, body: Vec<u8> },
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Resource {
     pub class_name: Name,
     pub name: Name,
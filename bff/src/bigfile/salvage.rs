@@ -0,0 +1,62 @@
+use std::io::{Read, Seek};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::ProgressObserver;
+use crate::bigfile::{probe, BigFile};
+use crate::names::NameContext;
+use crate::BffResult;
+
+#[derive(Serialize, Debug)]
+pub struct FailedRegion {
+    // None for data outside of the blocks, like the pool
+    pub block_index: Option<usize>,
+    pub offset: u64,
+    pub end: u64,
+    pub error: String,
+}
+
+#[derive(Debug)]
+pub struct SalvagedBigFile {
+    pub bigfile: BigFile,
+    pub failures: Vec<FailedRegion>,
+}
+
+// Readers share their parsing code between strict and salvage mode. Failures are only recorded
+// when there is somewhere to record them, otherwise they are returned.
+pub(crate) fn recover<T>(
+    failures: &mut Option<Vec<FailedRegion>>,
+    block_index: Option<usize>,
+    offset: u64,
+    end: u64,
+    result: BffResult<T>,
+) -> BffResult<Option<T>> {
+    match (result, failures) {
+        (Ok(value), _) => Ok(Some(value)),
+        (Err(err), Some(failures)) => {
+            failures.push(FailedRegion {
+                block_index,
+                offset,
+                end,
+                error: err.to_string(),
+            });
+            Ok(None)
+        }
+        (Err(err), None) => Err(err),
+    }
+}
+
+impl BigFile {
+    // Salvage a BigFile whose platform is not known, the hint is used if its endianness matches
+    pub fn read_salvage_auto<R: Read + Seek>(
+        reader: &mut R,
+        hint: Option<Platform>,
+        name_context: &NameContext,
+        observer: Option<Arc<dyn ProgressObserver>>,
+    ) -> BffResult<SalvagedBigFile> {
+        let (_, platform) = probe::probe(reader, hint)?;
+        Self::read_salvage_platform(reader, platform, name_context, observer)
+    }
+}
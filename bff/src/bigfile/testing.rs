@@ -0,0 +1,70 @@
+// Small in-memory BigFiles for the unit tests of the operations on BigFiles
+
+use crate::bigfile::manifest::{Manifest, ManifestBlock, ManifestObject};
+use crate::bigfile::platforms::Platform;
+use crate::bigfile::resource::Resource;
+use crate::bigfile::resource::ResourceData::SplitData;
+use crate::bigfile::BigFile;
+use crate::names::{Name, NameContext, NameType};
+
+pub const VERSION: &str = "v1.06.63.02 - Asobo Studio - Internal Cross Technology";
+
+pub fn name(string: &str) -> Name {
+    NameType::Asobo32.hash(string.as_bytes())
+}
+
+pub fn resource(name: Name, class_name: &str, body: &[u8]) -> Resource {
+    Resource {
+        class_name: self::name(class_name),
+        name,
+        link_name: None,
+        compress: false,
+        data: SplitData {
+            link_header: Vec::new(),
+            body: body.to_vec(),
+        },
+    }
+}
+
+pub fn block(names: &[Name]) -> ManifestBlock {
+    ManifestBlock {
+        offset: None,
+        checksum: None,
+        compressed: None,
+        objects: names
+            .iter()
+            .map(|name| ManifestObject {
+                name: *name,
+                compress: None,
+                pooled: None,
+                external: None,
+                unknowns: None,
+            })
+            .collect(),
+        resource_counts: None,
+        data: None,
+        unknowns: None,
+    }
+}
+
+pub fn bigfile(blocks: Vec<ManifestBlock>, resources: Vec<Resource>) -> BigFile {
+    BigFile {
+        manifest: Manifest {
+            version: VERSION.into(),
+            platform: Platform::PC,
+            version_xple: None,
+            bigfile_type: None,
+            pool_manifest_unused: None,
+            incredi_builder_string: None,
+            blocks,
+            pool: None,
+            header_unknowns: None,
+            compression: None,
+        },
+        objects: resources
+            .into_iter()
+            .map(|resource| (resource.name, resource))
+            .collect(),
+        name_context: NameContext::default(),
+    }
+}
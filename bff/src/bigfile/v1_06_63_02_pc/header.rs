@@ -4,7 +4,7 @@ use binrw::*;
 use serde::Serialize;
 
 use crate::bigfile::versions::VersionTriple;
-use crate::helpers::FixedStringNull;
+use crate::helpers::{checked_vec_parser, FixedStringNull};
 use crate::names::Name;
 
 #[derive(Serialize, Debug, BinRead, BinWrite)]
@@ -56,7 +56,7 @@ pub struct Header {
     pub padded_size: u32,
     pub version_triple: VersionTriple,
     #[serde(skip)]
-    #[br(count = block_count)]
+    #[br(parse_with = checked_vec_parser, args(block_count as usize))]
     pub block_descriptions: Vec<BlockDescription>,
     #[br(ignore)]
     pub tag: Option<Vec<u8>>,
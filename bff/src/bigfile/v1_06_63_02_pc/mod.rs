@@ -1,75 +1,265 @@
-pub mod block;
 pub mod header;
 pub mod object;
+pub mod patch;
 pub mod pool;
 
 use std::cmp::max;
 use std::collections::{HashMap, HashSet};
 use std::default::Default;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
 use binrw::{BinRead, BinResult, BinWrite, Endian};
-use block::Block;
 use header::{BlockDescription, Header};
-use object::Object;
+use object::{CompressedBlockObject, CompressedObject, Object, ObjectHeader};
 use pool::Pool;
 
+use crate::bigfile::compression::record_object;
+use crate::bigfile::lazy::{BigFileIndex, IndexedObject, ObjectLocation};
 use crate::bigfile::manifest::*;
 use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::{report, report_objects, Progress};
 use crate::bigfile::resource::Resource;
 use crate::bigfile::resource::ResourceData::SplitData;
+use crate::bigfile::salvage::{recover, FailedRegion, SalvagedBigFile};
 use crate::bigfile::v1_06_63_02_pc::pool::{
     calculate_padded_pool_header_size,
+    check_pooled_flags,
+    generate_manifest_pool,
+    mark_pooled_objects,
     ObjectDescription,
     PoolHeader,
     ReferenceRecord,
 };
+use crate::bigfile::verify::ChecksumMismatch;
 use crate::bigfile::versions::{Version, VersionXple};
 use crate::bigfile::BigFile;
-use crate::helpers::{calculated_padded, write_align_to};
-use crate::lz::lzrs_compress_data_with_header_writer_internal;
+use crate::crc::AsoboAlternate32;
+use crate::error::{
+    BlockParseError,
+    ObjectNotFoundError,
+    ObjectParseError,
+    PoolIndexError,
+    PoolOffsetMismatchError,
+    UnreferencedPoolObjectError,
+    UnsplitObjectError,
+};
+use crate::helpers::{
+    calculated_padded,
+    check_count,
+    expect_end,
+    par_map,
+    read_align_to,
+    write_align_to,
+};
+use crate::lz::lzrs_compress_data_with_header;
 use crate::names::NameType::Asobo32;
-use crate::names::{Name, NameType};
-use crate::traits::BigFileIo;
+use crate::names::{Name, NameContext, NameType};
+use crate::traits::{BigFileIo, NameHashFunction, SetLen};
 use crate::BffResult;
 
-#[binrw::parser(reader, endian)]
-pub fn blocks_parser(
-    block_descriptions: Vec<BlockDescription>,
+// Objects are read one at a time so a failure can name the object it happened in
+fn compressed_object_parser<O: CompressedBlockObject, R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    block_index: usize,
+) -> BffResult<(u64, (Name, Name), O)> {
+    let offset = reader.stream_position()?;
+    let header: ObjectHeader = O::Header::read_options(reader, endian, ())
+        .map_err(|err| BlockParseError::new(offset, block_index, Box::new(err.into())))?
+        .into();
+    let (name, class_name) = (header.name, header.class_name);
+    reader.seek(SeekFrom::Start(offset))?;
+    let object = O::read_options(reader, endian, ()).map_err(|err| {
+        ObjectParseError::new(offset, block_index, name, class_name, Box::new(err.into()))
+    })?;
+    Ok((offset, (name, class_name), object))
+}
+
+// Shared by the versions whose blocks are a list of objects padded to a sector
+pub(crate) fn block_objects_parser<O: CompressedBlockObject, R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    block_index: usize,
+    object_count: u32,
     objects: &mut HashMap<Name, Resource>,
-) -> BinResult<Vec<ManifestBlock>> {
-    let mut blocks: Vec<ManifestBlock> = Vec::with_capacity(block_descriptions.len());
-
-    for block_description in block_descriptions {
-        let block = Block::read_options(reader, endian, (&block_description,))?;
-        let mut block_objects = Vec::with_capacity(block.objects.len());
-        for object in block.objects.into_iter() {
-            block_objects.push(ManifestObject {
-                name: object.name,
-                compress: Some(object.compress),
-            });
+    block_objects: &mut Vec<ManifestObject>,
+) -> BffResult<()> {
+    let block_offset = reader.stream_position()?;
+    let object_count = check_count(reader, object_count as usize)
+        .map_err(|err| BlockParseError::new(block_offset, block_index, Box::new(err.into())))?;
+    block_objects.reserve(object_count);
+
+    // Reading stops at the first object that fails, the objects read before it are still kept
+    let mut compressed_objects = Vec::with_capacity(object_count);
+    let result: BffResult<()> = (0..object_count).try_for_each(|_| {
+        compressed_objects.push(compressed_object_parser::<O, _>(
+            reader,
+            endian,
+            block_index,
+        )?);
+        Ok(())
+    });
+    if result.is_ok() {
+        read_align_to(reader, 2048)?;
+    }
 
-            objects.insert(object.name, object.into());
+    // Decompress the bodies in parallel, the objects before the first failure are kept
+    let resources = par_map(
+        compressed_objects,
+        |(offset, (name, class_name), object)| {
+            object.into_resource(endian).map_err(|err| {
+                ObjectParseError::new(offset, block_index, name, class_name, Box::new(err.into()))
+            })
+        },
+    );
+
+    for resource in resources {
+        let resource = resource?;
+
+        block_objects.push(ManifestObject {
+            name: resource.name,
+            compress: Some(resource.compress),
+            pooled: None,
+            external: None,
+            unknowns: None,
+        });
+
+        objects.insert(resource.name, resource);
+    }
+
+    result
+}
+
+// Only the object headers of a block are read, the objects are read on demand from their offset
+pub(crate) fn block_objects_index_parser<O: CompressedBlockObject, R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    object_count: u32,
+    objects: &mut HashMap<Name, IndexedObject>,
+    block_objects: &mut Vec<ManifestObject>,
+) -> BffResult<()> {
+    let object_count = check_count(reader, object_count as usize)?;
+    block_objects.reserve(object_count);
+
+    for _ in 0..object_count {
+        let offset = reader.stream_position()?;
+        let object: ObjectHeader = O::Header::read_options(reader, endian, ())?.into();
+
+        block_objects.push(ManifestObject {
+            name: object.name,
+            compress: Some(object.compress),
+            pooled: None,
+            external: None,
+            unknowns: None,
+        });
+
+        objects.insert(
+            object.name,
+            IndexedObject::stored(object.class_name, object.size, offset),
+        );
+    }
+    read_align_to(reader, 2048)?;
+
+    Ok(())
+}
+
+// Walk the blocks of the versions that share the block descriptions of v1.06.63.02, read_objects
+// reads the objects of a block whole or only their headers. In salvage mode a block that fails to
+// parse keeps the objects read before the failure and the next block is read from where its
+// description says it starts.
+pub(crate) fn walk_blocks<R: Read + Seek>(
+    reader: &mut R,
+    block_descriptions: Vec<BlockDescription>,
+    failures: &mut Option<Vec<FailedRegion>>,
+    mut read_objects: impl FnMut(&mut R, usize, u32, &mut Vec<ManifestObject>) -> BffResult<()>,
+) -> BffResult<Vec<ManifestBlock>> {
+    let block_count = block_descriptions.len();
+    let mut blocks: Vec<ManifestBlock> = Vec::with_capacity(block_count);
+
+    for (block_index, block_description) in block_descriptions.into_iter().enumerate() {
+        report(Progress::Block {
+            index: block_index,
+            count: block_count,
+        })?;
+
+        let block_offset = reader.stream_position()?;
+        let block_end = block_offset + block_description.padded_size as u64;
+        let mut block_objects = Vec::new();
+        let result = read_objects(
+            reader,
+            block_index,
+            block_description.object_count,
+            &mut block_objects,
+        );
+        if recover(failures, Some(block_index), block_offset, block_end, result)?.is_none() {
+            reader.seek(SeekFrom::Start(block_end))?;
         }
 
+        report_objects(block_objects.iter().map(|object| object.name))?;
+
         blocks.push(ManifestBlock {
             offset: Some(block_description.working_buffer_offset as u64),
             checksum: block_description.checksum,
             compressed: None,
             objects: block_objects,
+            resource_counts: None,
+            data: None,
+            unknowns: None,
         });
     }
 
     Ok(blocks)
 }
 
-#[binrw::parser(reader, endian)]
-fn pool_parser(objects: &mut HashMap<Name, Resource>) -> BinResult<ManifestPool> {
-    let pool = Pool::read_options(reader, endian, ())?;
+pub fn blocks_parser<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    block_descriptions: Vec<BlockDescription>,
+    objects: &mut HashMap<Name, Resource>,
+    failures: &mut Option<Vec<FailedRegion>>,
+) -> BffResult<Vec<ManifestBlock>> {
+    walk_blocks(
+        reader,
+        block_descriptions,
+        failures,
+        |reader, block_index, object_count, block_objects| {
+            block_objects_parser::<CompressedObject, _>(
+                reader,
+                endian,
+                block_index,
+                object_count,
+                objects,
+                block_objects,
+            )
+        },
+    )
+}
+
+pub fn blocks_index_parser<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    block_descriptions: Vec<BlockDescription>,
+    objects: &mut HashMap<Name, IndexedObject>,
+) -> BffResult<Vec<ManifestBlock>> {
+    walk_blocks(
+        reader,
+        block_descriptions,
+        &mut None,
+        |reader, _, object_count, block_objects| {
+            block_objects_index_parser::<CompressedObject, _>(
+                reader,
+                endian,
+                object_count,
+                objects,
+                block_objects,
+            )
+        },
+    )
+}
 
-    let object_entry_indices = pool.header.object_descriptions_indices.inner;
-    let object_entries = pool
-        .header
+fn manifest_pool(header: PoolHeader) -> ManifestPool {
+    let object_entry_indices = header.object_descriptions_indices.inner;
+    let object_entries = header
         .object_descriptions
         .iter()
         .map(|x| ManifestPoolObjectEntry {
@@ -77,8 +267,7 @@ fn pool_parser(objects: &mut HashMap<Name, Resource>) -> BinResult<ManifestPool>
             reference_record_index: x.reference_records_index,
         })
         .collect::<Vec<_>>();
-    let reference_records = pool
-        .header
+    let reference_records = header
         .reference_records
         .iter()
         .map(|x| ManifestPoolReferenceRecord {
@@ -87,52 +276,330 @@ fn pool_parser(objects: &mut HashMap<Name, Resource>) -> BinResult<ManifestPool>
         })
         .collect::<Vec<_>>();
 
+    ManifestPool {
+        object_entry_indices,
+        object_entries,
+        reference_records,
+    }
+}
+
+#[binrw::parser(reader, endian)]
+fn pool_index_parser(objects: &mut HashMap<Name, IndexedObject>) -> BinResult<ManifestPool> {
+    let header = PoolHeader::read_options(reader, endian, ())?;
+
+    for _ in 0..header.object_descriptions_indices.len() {
+        let offset = reader.stream_position()?;
+        let pool_object = ObjectHeader::read_options(reader, endian, ())?;
+        read_align_to(reader, 2048)?;
+
+        // The same object may appear more than once in the pool
+        if let Some(IndexedObject {
+            size,
+            location:
+                ObjectLocation::Stored {
+                    body_offset: body_offset @ None,
+                    ..
+                },
+            ..
+        }) = objects.get_mut(&pool_object.name)
+        {
+            *size += pool_object.size;
+            *body_offset = Some(offset);
+        }
+    }
+
+    Ok(manifest_pool(header))
+}
+
+#[binrw::parser(reader, endian)]
+fn pool_parser(objects: &mut HashMap<Name, Resource>) -> BinResult<ManifestPool> {
+    let pool_offset = reader.stream_position()?;
+    let pool = Pool::read_options(reader, endian, ())?;
+
     for pool_object in pool.objects.into_iter() {
         let name = pool_object.object.name;
-        let object = objects.get_mut(&name).unwrap();
-        object.compress = pool_object.object.compress;
-        match &mut object.data {
-            SplitData { body, .. } => {
-                *body = pool_object.object.body;
+        let Some(Resource {
+            compress,
+            data: SplitData { body, .. },
+            ..
+        }) = objects.get_mut(&name)
+        else {
+            return Err(binrw::Error::Custom {
+                pos: pool_offset,
+                err: Box::new(ObjectNotFoundError::new(name)),
+            });
+        };
+        *compress = pool_object.object.compress;
+        *body = pool_object.object.body;
+    }
+
+    Ok(manifest_pool(pool.header))
+}
+
+pub fn write_block_objects<'a, W: Write + Seek>(
+    writer: &mut W,
+    endian: Endian,
+    block_index: usize,
+    objects: impl IntoIterator<Item = (&'a Resource, bool)>,
+) -> BffResult<usize> {
+    let block_begin = writer.stream_position()?;
+    let mut calculated_working_buffer_offset = 0usize;
+
+    // Compress the bodies in parallel, the objects are then written in order
+    let objects = objects.into_iter().collect::<Vec<_>>();
+    let bodies = objects
+        .iter()
+        .map(
+            |&(resource, is_pooled)| match (&resource.data, is_pooled, resource.compress) {
+                (SplitData { body, .. }, false, true) => Some(body.as_slice()),
+                _ => None,
+            },
+        )
+        .collect::<Vec<_>>();
+    let compressed_bodies = par_map(bodies, |body| {
+        body.map(|body| lzrs_compress_data_with_header(body, endian))
+            .transpose()
+    });
+
+    for ((resource, is_pooled), compressed_body) in objects.into_iter().zip(compressed_bodies) {
+        let begin_resource = writer.stream_position()?;
+        match (
+            &resource.data,
+            is_pooled,
+            resource.compress,
+            compressed_body?,
+        ) {
+            (SplitData { link_header, body }, false, true, Some(compressed_body)) => {
+                let compressed_body_size = compressed_body.len() as u32;
+                (link_header.len() as u32 + compressed_body_size).write_options(
+                    writer,
+                    endian,
+                    (),
+                )?;
+                (link_header.len() as u32).write_options(writer, endian, ())?;
+                (body.len() as u32).write_options(writer, endian, ())?;
+                compressed_body_size.write_options(writer, endian, ())?;
+                resource.class_name.write_options(writer, endian, ())?;
+                resource.name.write_options(writer, endian, ())?;
+                writer.write_all(link_header)?;
+                writer.write_all(&compressed_body)?;
+                record_object(
+                    Some(block_index),
+                    resource.class_name,
+                    resource.size(),
+                    link_header.len() + compressed_body.len(),
+                );
+                report(Progress::Object {
+                    name: resource.name,
+                })?;
+
+                let needed_working_buffer_offset =
+                    if body.len() > (begin_resource - block_begin) as usize {
+                        body.len()
+                    } else {
+                        0
+                    };
+
+                calculated_working_buffer_offset = max(
+                    needed_working_buffer_offset,
+                    calculated_working_buffer_offset,
+                );
             }
-            _ => unreachable!(),
+            (SplitData { link_header, body }, false, false, _) => {
+                (link_header.len() as u32 + body.len() as u32).write_options(writer, endian, ())?;
+                (link_header.len() as u32).write_options(writer, endian, ())?;
+                (body.len() as u32).write_options(writer, endian, ())?;
+                0u32.write_options(writer, endian, ())?;
+                resource.class_name.write_options(writer, endian, ())?;
+                resource.name.write_options(writer, endian, ())?;
+                writer.write_all(link_header)?;
+                writer.write_all(body)?;
+                record_object(
+                    Some(block_index),
+                    resource.class_name,
+                    resource.size(),
+                    resource.size(),
+                );
+                report(Progress::Object {
+                    name: resource.name,
+                })?;
+            }
+            (SplitData { link_header, .. }, true, _, _) => {
+                (link_header.len() as u32).write_options(writer, endian, ())?;
+                (link_header.len() as u32).write_options(writer, endian, ())?;
+                0u32.write_options(writer, endian, ())?;
+                0u32.write_options(writer, endian, ())?;
+                resource.class_name.write_options(writer, endian, ())?;
+                resource.name.write_options(writer, endian, ())?;
+                writer.write_all(link_header)?;
+                // The body is counted with the pool
+                record_object(
+                    Some(block_index),
+                    resource.class_name,
+                    link_header.len(),
+                    link_header.len(),
+                );
+                report(Progress::Object {
+                    name: resource.name,
+                })?;
+            }
+            _ => return Err(UnsplitObjectError::new(resource.name).into()),
         }
     }
 
-    Ok(ManifestPool {
-        object_entry_indices,
-        object_entries,
-        reference_records,
-    })
+    Ok(calculated_working_buffer_offset)
+}
+
+// The checksum covers the unpadded block
+pub fn block_checksum(data: &[u8]) -> i32 {
+    AsoboAlternate32::hash(data)
+}
+
+// Blocks are laid out back to back after the header
+pub fn verify_blocks<R: Read + Seek>(
+    reader: &mut R,
+    block_descriptions: &[BlockDescription],
+) -> BffResult<Vec<ChecksumMismatch>> {
+    let mut mismatches = Vec::new();
+    let mut offset = reader.stream_position()?;
+
+    for (block_index, block_description) in block_descriptions.iter().enumerate() {
+        if let Some(expected) = block_description.checksum {
+            let mut data = vec![0; block_description.data_size as usize];
+            reader.seek(SeekFrom::Start(offset))?;
+            reader.read_exact(&mut data)?;
+            let actual = block_checksum(&data);
+            if actual != expected {
+                mismatches.push(ChecksumMismatch {
+                    block_index,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        offset += block_description.padded_size as u64;
+    }
+
+    Ok(mismatches)
 }
 
 pub struct BigFileV1_06_63_02PC;
 
+impl BigFileV1_06_63_02PC {
+    fn read_with_failures<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+        failures: &mut Option<Vec<FailedRegion>>,
+    ) -> BffResult<BigFile> {
+        let endian = platform.into();
+        let header = Header::read_options(reader, endian, ())?;
+
+        let mut objects = HashMap::new();
+
+        let mut blocks = blocks_parser(
+            reader,
+            endian,
+            header.block_descriptions,
+            &mut objects,
+            failures,
+        )?;
+
+        let pool = if let Some(pool_offset) = header.pool_offset {
+            let pool_offset = pool_offset as u64;
+            let offset = reader.stream_position()?;
+            if pool_offset != offset {
+                let result = Err(PoolOffsetMismatchError::new(pool_offset, offset).into());
+                recover::<()>(failures, None, offset, pool_offset, result)?;
+            }
+
+            let len = reader.seek(SeekFrom::End(0))?;
+            reader.seek(SeekFrom::Start(pool_offset))?;
+            let result = pool_parser(reader, endian, (&mut objects,)).map_err(Into::into);
+            let pool = recover(failures, None, pool_offset, len, result)?;
+            if pool.is_none() {
+                reader.seek(SeekFrom::Start(len))?;
+            }
+            pool
+        } else {
+            None
+        };
+
+        if let Some(pool) = &pool {
+            mark_pooled_objects(&mut blocks, pool);
+        }
+
+        let offset = reader.stream_position()?;
+        let result = expect_end(reader);
+        let len = reader.stream_position()?;
+        recover(failures, None, offset, len, result)?;
+
+        Ok(BigFile {
+            manifest: Manifest {
+                version,
+                version_xple: Some(header.version_triple.into()),
+                platform,
+                bigfile_type: Some(header.bigfile_type.into()),
+                pool_manifest_unused: header.pool_manifest_unused,
+                incredi_builder_string: header.incredi_builder_string,
+                blocks,
+                pool,
+                header_unknowns: None,
+                compression: None,
+            },
+            objects,
+            name_context: NameContext::current(),
+        })
+    }
+}
+
 impl BigFileIo for BigFileV1_06_63_02PC {
     fn read<R: Read + Seek>(
         reader: &mut R,
         version: Version,
         platform: Platform,
     ) -> BffResult<BigFile> {
+        Self::read_with_failures(reader, version, platform, &mut None)
+    }
+
+    fn read_salvage<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<SalvagedBigFile> {
+        let mut failures = Some(Vec::new());
+        let bigfile = Self::read_with_failures(reader, version, platform, &mut failures)?;
+        Ok(SalvagedBigFile {
+            bigfile,
+            failures: failures.unwrap_or_default(),
+        })
+    }
+
+    fn read_index<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFileIndex> {
         let endian = platform.into();
         let header = Header::read_options(reader, endian, ())?;
 
         let mut objects = HashMap::new();
 
-        let blocks = blocks_parser(reader, endian, (header.block_descriptions, &mut objects))?;
+        let mut blocks =
+            blocks_index_parser(reader, endian, header.block_descriptions, &mut objects)?;
 
         let pool = if let Some(pool_offset) = header.pool_offset {
-            assert_eq!(pool_offset as u64, reader.stream_position().unwrap());
-            Some(pool_parser(reader, endian, (&mut objects,))?)
+            reader.seek(SeekFrom::Start(pool_offset as u64))?;
+            Some(pool_index_parser(reader, endian, (&mut objects,))?)
         } else {
             None
         };
 
-        let pos = reader.stream_position().unwrap();
-        let len = reader.seek(SeekFrom::End(0)).unwrap();
-        assert_eq!(pos, len);
+        if let Some(pool) = &pool {
+            mark_pooled_objects(&mut blocks, pool);
+        }
 
-        Ok(BigFile {
+        Ok(BigFileIndex {
             manifest: Manifest {
                 version,
                 version_xple: Some(header.version_triple.into()),
@@ -142,8 +609,11 @@ impl BigFileIo for BigFileV1_06_63_02PC {
                 incredi_builder_string: header.incredi_builder_string,
                 blocks,
                 pool,
+                header_unknowns: None,
+                compression: None,
             },
             objects,
+            name_context: NameContext::current(),
         })
     }
 
@@ -161,93 +631,50 @@ impl BigFileIo for BigFileV1_06_63_02PC {
         let mut block_working_buffer_capacity_odd = 0u32;
         let mut block_sector_padding_size = 0u32;
 
-        let pooled = bigfile
-            .manifest
-            .pool
-            .as_ref()
-            .map_or_else(HashSet::new, |pool| {
-                let mut pooled = HashSet::new();
-                for r in pool.object_entries.iter() {
-                    pooled.insert(r.name);
-                }
+        let generated_pool = match &bigfile.manifest.pool {
+            Some(pool) => {
+                check_pooled_flags(&bigfile.manifest.blocks, pool)?;
+                None
+            }
+            None => generate_manifest_pool(&bigfile.manifest.blocks),
+        };
+        let pool = bigfile.manifest.pool.as_ref().or(generated_pool.as_ref());
 
-                pooled
-            });
+        let pooled = pool.map_or_else(HashSet::new, |pool| {
+            let mut pooled = HashSet::new();
+            for r in pool.object_entries.iter() {
+                pooled.insert(r.name);
+            }
+
+            pooled
+        });
 
         let mut block_descriptions = Vec::with_capacity(bigfile.manifest.blocks.len());
 
         for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
-            let block_begin = writer.stream_position()?;
-
-            let mut calculated_working_buffer_offset = 0usize;
-
-            for object in block.objects.iter() {
-                let resource = bigfile.objects.get(&object.name).unwrap();
-                let is_pooled = pooled.contains(&object.name);
-                let begin_resource = writer.stream_position()?;
-                match (&resource.data, is_pooled, resource.compress) {
-                    (SplitData { link_header, body }, false, true) => {
-                        let begin_header = writer.stream_position()?;
-                        writer.seek(SeekFrom::Current(24))?;
-                        writer.write_all(link_header)?;
-                        let begin_body = writer.stream_position()?;
-                        lzrs_compress_data_with_header_writer_internal(body, writer, endian, ())?;
-                        let end_body = writer.stream_position()?;
-                        writer.seek(SeekFrom::Start(begin_header))?;
-                        let compressed_body_size = (end_body - begin_body) as u32;
-                        (link_header.len() as u32 + compressed_body_size).write_options(
-                            writer,
-                            endian,
-                            (),
-                        )?;
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        (body.len() as u32).write_options(writer, endian, ())?;
-                        compressed_body_size.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.seek(SeekFrom::Start(end_body))?;
-
-                        let needed_working_buffer_offset =
-                            if body.len() > (begin_resource - block_begin) as usize {
-                                body.len()
-                            } else {
-                                0
-                            };
-
-                        calculated_working_buffer_offset = max(
-                            needed_working_buffer_offset,
-                            calculated_working_buffer_offset,
-                        );
-                    }
-                    (SplitData { link_header, body }, false, false) => {
-                        (link_header.len() as u32 + body.len() as u32).write_options(
-                            writer,
-                            endian,
-                            (),
-                        )?;
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        (body.len() as u32).write_options(writer, endian, ())?;
-                        0u32.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.write_all(link_header)?;
-                        writer.write_all(body)?;
-                    }
-                    (SplitData { link_header, .. }, true, _) => {
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        0u32.write_options(writer, endian, ())?;
-                        0u32.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.write_all(link_header)?;
-                    }
-                    _ => todo!(),
-                }
-            }
-
-            let block_end = writer.stream_position()?;
-            let data_size = (block_end - block_begin) as u32;
+            report(Progress::Block {
+                index: i,
+                count: bigfile.manifest.blocks.len(),
+            })?;
+
+            let mut block_writer = Cursor::new(Vec::new());
+
+            let calculated_working_buffer_offset = write_block_objects(
+                &mut block_writer,
+                endian,
+                i,
+                block
+                    .objects
+                    .iter()
+                    .map(|object| {
+                        Ok((bigfile.object(&object.name)?, pooled.contains(&object.name)))
+                    })
+                    .collect::<BffResult<Vec<_>>>()?,
+            )?;
+
+            let block_data = block_writer.into_inner();
+            writer.write_all(&block_data)?;
+            let data_size = block_data.len() as u32;
             let padding = write_align_to(writer, 2048, 0x00)?;
             let padded_size = data_size + padding as u32;
 
@@ -278,8 +705,7 @@ impl BigFileIo for BigFileV1_06_63_02PC {
                 data_size,
                 working_buffer_offset,
                 first_object_name: block.objects.first().map(|r| r.name).unwrap_or_default(),
-                // TODO: Calculate checksum using Asobo Alternate on the unpadded block while writing
-                checksum: block.checksum,
+                checksum: Some(block_checksum(&block_data)),
             });
         }
 
@@ -288,7 +714,7 @@ impl BigFileIo for BigFileV1_06_63_02PC {
             pool_sector_padding_size,
             pool_object_decompression_buffer_capacity,
             pool_manifest_padded_size,
-        ) = if let Some(pool) = &bigfile.manifest.pool {
+        ) = if let Some(pool) = pool {
             let begin_pool_header = writer.stream_position()?;
 
             let objects_names_count_sum = pool
@@ -312,33 +738,63 @@ impl BigFileIo for BigFileV1_06_63_02PC {
             let mut pool_sector_padding_size = 0u32;
             let mut pool_object_decompression_buffer_capacity = 0;
 
-            for i in pool.object_entry_indices.iter() {
-                let entry = pool.object_entries.get(*i as usize).unwrap();
-                let name = entry.name;
-                let resource = bigfile.objects.get(&name).unwrap();
+            // The pool manifest may have been edited by hand, its indices are checked
+            let indexed_names = pool
+                .object_entry_indices
+                .iter()
+                .map(|i| {
+                    pool.object_entries
+                        .get(*i as usize)
+                        .map(|entry| entry.name)
+                        .ok_or_else(|| {
+                            PoolIndexError::new(
+                                "object entry",
+                                *i as usize,
+                                pool.object_entries.len(),
+                            )
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let pool_resources = indexed_names
+                .iter()
+                .map(|name| bigfile.object(name))
+                .collect::<Result<Vec<_>, _>>()?;
+            let bodies = pool_resources
+                .iter()
+                .map(|resource| match (&resource.data, resource.compress) {
+                    (SplitData { body, .. }, true) => Some(body.as_slice()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let compressed_bodies = par_map(bodies, |body| {
+                body.map(|body| lzrs_compress_data_with_header(body, endian))
+                    .transpose()
+            });
+
+            for (resource, compressed_body) in pool_resources.into_iter().zip(compressed_bodies) {
+                let name = resource.name;
                 let begin_resource = writer.stream_position()?;
-                match (&resource.data, resource.compress) {
-                    (SplitData { body, .. }, true) => {
-                        let begin_header = writer.stream_position()?;
-                        writer.seek(SeekFrom::Current(24))?;
-                        let begin_body = writer.stream_position()?;
-                        lzrs_compress_data_with_header_writer_internal(body, writer, endian, ())?;
-                        let end_body = writer.stream_position()?;
-                        writer.seek(SeekFrom::Start(begin_header))?;
-                        let compressed_body_size = (end_body - begin_body) as u32;
+                match (&resource.data, resource.compress, compressed_body?) {
+                    (SplitData { body, .. }, true, Some(compressed_body)) => {
+                        let compressed_body_size = compressed_body.len() as u32;
                         compressed_body_size.write_options(writer, endian, ())?;
                         0u32.write_options(writer, endian, ())?;
                         (body.len() as u32).write_options(writer, endian, ())?;
                         compressed_body_size.write_options(writer, endian, ())?;
                         resource.class_name.write_options(writer, endian, ())?;
                         resource.name.write_options(writer, endian, ())?;
-                        writer.seek(SeekFrom::Start(end_body))?;
+                        writer.write_all(&compressed_body)?;
+                        record_object(None, resource.class_name, body.len(), compressed_body.len());
+                        report(Progress::Object {
+                            name: resource.name,
+                        })?;
                         pool_object_decompression_buffer_capacity = max(
                             (calculated_padded(body.len(), 2048)) / 2048,
                             pool_object_decompression_buffer_capacity,
                         );
                     }
-                    (SplitData { body, .. }, false) => {
+                    (SplitData { body, .. }, false, _) => {
                         (body.len() as u32).write_options(writer, endian, ())?;
                         0u32.write_options(writer, endian, ())?;
                         (body.len() as u32).write_options(writer, endian, ())?;
@@ -346,12 +802,16 @@ impl BigFileIo for BigFileV1_06_63_02PC {
                         resource.class_name.write_options(writer, endian, ())?;
                         resource.name.write_options(writer, endian, ())?;
                         writer.write_all(body)?;
+                        record_object(None, resource.class_name, body.len(), body.len());
+                        report(Progress::Object {
+                            name: resource.name,
+                        })?;
                         pool_object_decompression_buffer_capacity = max(
                             (calculated_padded(body.len(), 2048)) / 2048,
                             pool_object_decompression_buffer_capacity,
                         );
                     }
-                    _ => todo!(),
+                    _ => return Err(UnsplitObjectError::new(name).into()),
                 }
                 let end_resource = writer.stream_position()?;
                 let resource_size = (end_resource - begin_resource) as u32;
@@ -364,20 +824,28 @@ impl BigFileIo for BigFileV1_06_63_02PC {
             let pool_data_end = writer.stream_position()?;
             writer.seek(SeekFrom::Start(begin_pool_header))?;
 
+            let padded_size = |name: &Name| {
+                object_padded_sizes
+                    .get(name)
+                    .copied()
+                    .ok_or(UnreferencedPoolObjectError::new(*name))
+            };
+
             let object_descriptions = pool
                 .object_entries
                 .iter()
-                .map(|x| ObjectDescription {
-                    name: x.name,
-                    reference_count: pool
-                        .object_entry_indices
-                        .iter()
-                        .filter(|y| pool.object_entries.get(**y as usize).unwrap().name == x.name)
-                        .count() as u32,
-                    padded_size: *object_padded_sizes.get(&x.name).unwrap(),
-                    reference_records_index: x.reference_record_index,
+                .map(|x| {
+                    Ok(ObjectDescription {
+                        name: x.name,
+                        reference_count: indexed_names
+                            .iter()
+                            .filter(|name| **name == x.name)
+                            .count() as u32,
+                        padded_size: padded_size(&x.name)?,
+                        reference_records_index: x.reference_record_index,
+                    })
                 })
-                .collect::<Vec<_>>();
+                .collect::<BffResult<Vec<_>>>()?;
 
             let start_chunk = (end_pool_header / 2048) as u32;
 
@@ -391,28 +859,30 @@ impl BigFileIo for BigFileV1_06_63_02PC {
                     let first = objects_name_starting_index as usize;
                     let last = objects_name_starting_index as usize + objects_name_count as usize;
 
-                    let get_object_padded_size = |x: usize| -> u32 {
-                        let object_entry_index = *pool.object_entry_indices.get(x).unwrap();
-                        let object_entry = pool
-                            .object_entries
-                            .get(object_entry_index as usize)
-                            .unwrap();
-                        *object_padded_sizes.get(&object_entry.name).unwrap()
+                    let get_object_padded_size = |x: usize| -> BffResult<u32> {
+                        let name = indexed_names.get(x).ok_or_else(|| {
+                            PoolIndexError::new("object entry index", x, indexed_names.len())
+                        })?;
+                        Ok(padded_size(name)?)
                     };
 
-                    let start_chunk_index =
-                        start_chunk + (0..first).map(get_object_padded_size).sum::<u32>();
-                    let end_chunk_index =
-                        start_chunk_index + (first..last).map(get_object_padded_size).sum::<u32>();
+                    let start_chunk_index = start_chunk
+                        + (0..first)
+                            .map(get_object_padded_size)
+                            .sum::<BffResult<u32>>()?;
+                    let end_chunk_index = start_chunk_index
+                        + (first..last)
+                            .map(get_object_padded_size)
+                            .sum::<BffResult<u32>>()?;
 
-                    ReferenceRecord {
+                    Ok(ReferenceRecord {
                         start_chunk_index,
                         end_chunk_index,
                         objects_name_starting_index,
                         objects_name_count,
-                    }
+                    })
                 })
-                .collect::<Vec<_>>();
+                .collect::<BffResult<Vec<_>>>()?;
 
             let pool_header = PoolHeader {
                 objects_names_count_sum,
@@ -478,6 +948,24 @@ impl BigFileIo for BigFileV1_06_63_02PC {
         Ok(())
     }
 
+    fn patch<S: Read + Write + Seek + SetLen>(
+        stream: &mut S,
+        _version: Version,
+        platform: Platform,
+        resource: &Resource,
+    ) -> BffResult<u64> {
+        patch::patch(stream, platform.into(), resource)
+    }
+
+    fn verify<R: Read + Seek>(
+        reader: &mut R,
+        _version: Version,
+        platform: Platform,
+    ) -> BffResult<Vec<ChecksumMismatch>> {
+        let header = Header::read_options(reader, platform.into(), ())?;
+        verify_blocks(reader, &header.block_descriptions)
+    }
+
     const NAME_TYPE: NameType = Asobo32;
 
     type ResourceType = Object;
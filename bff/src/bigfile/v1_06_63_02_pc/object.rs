@@ -1,24 +1,19 @@
-use std::io::{Read, Seek, Write};
+use std::io::{Cursor, Read, Seek, Write};
 
-use binrw::{args, binread, parser, BinRead, BinResult, BinWrite, Endian};
+use binrw::{binread, parser, BinRead, BinResult, BinWrite, Endian};
 use derive_more::{Deref, DerefMut};
 use serde::Serialize;
 
 use crate::bigfile::resource::Resource;
 use crate::bigfile::resource::ResourceData::SplitData;
+use crate::helpers::checked_vec_parser;
 use crate::lz::lzrs_decompress_body_parser;
 use crate::names::Name;
 
 #[parser(reader, endian)]
 pub fn body_parser(decompressed_size: u32, compressed_size: u32) -> BinResult<Vec<u8>> {
     if compressed_size == 0 {
-        Vec::<u8>::read_options(
-            reader,
-            endian,
-            args! {
-                count: decompressed_size as usize,
-            },
-        )
+        checked_vec_parser(reader, endian, (decompressed_size as usize,))
     } else {
         lzrs_decompress_body_parser(reader, endian, (decompressed_size, compressed_size))
     }
@@ -39,7 +34,7 @@ pub struct Object {
     pub compress: bool,
     pub class_name: Name,
     pub name: Name,
-    #[br(count = link_header_size)]
+    #[br(parse_with = checked_vec_parser, args(link_header_size as usize))]
     #[serde(skip_serializing)]
     pub link_header: Vec<u8>,
     #[br(parse_with = body_parser, args(decompressed_size, compressed_size))]
@@ -47,6 +42,80 @@ pub struct Object {
     pub body: Vec<u8>,
 }
 
+// An object whose body is read but not yet decompressed, so the bodies of a block can be
+// decompressed in parallel once it has been read
+#[binread]
+#[derive(Debug)]
+pub struct CompressedObject {
+    #[br(temp)]
+    _data_size: u32,
+    #[br(temp)]
+    link_header_size: u32,
+    decompressed_size: u32,
+    compressed_size: u32,
+    pub class_name: Name,
+    pub name: Name,
+    #[br(parse_with = checked_vec_parser, args(link_header_size as usize))]
+    pub link_header: Vec<u8>,
+    #[br(parse_with = checked_vec_parser, args(if compressed_size != 0 { compressed_size } else { decompressed_size } as usize))]
+    body: Vec<u8>,
+}
+
+impl CompressedObject {
+    pub fn decompress(self, endian: Endian) -> BinResult<Object> {
+        let body = body_parser(
+            &mut Cursor::new(self.body),
+            endian,
+            (self.decompressed_size, self.compressed_size),
+        )?;
+        Ok(Object {
+            compress: self.compressed_size != 0,
+            class_name: self.class_name,
+            name: self.name,
+            link_header: self.link_header,
+            body,
+        })
+    }
+}
+
+// The compressed objects of the versions that share the blocks of v1.06.63.02
+pub(crate) trait CompressedBlockObject: for<'a> BinRead<Args<'a> = ()> + Send {
+    // Read before the object so a failure can name the object it happened in, or alone when only
+    // an index is read
+    type Header: for<'a> BinRead<Args<'a> = ()> + Into<ObjectHeader>;
+
+    fn into_resource(self, endian: Endian) -> BinResult<Resource>;
+}
+
+impl CompressedBlockObject for CompressedObject {
+    type Header = ObjectHeader;
+
+    fn into_resource(self, endian: Endian) -> BinResult<Resource> {
+        Ok(self.decompress(endian)?.into())
+    }
+}
+
+#[binread]
+#[derive(Debug)]
+pub struct ObjectHeader {
+    #[br(temp)]
+    _data_size: u32,
+    #[br(temp)]
+    link_header_size: u32,
+    #[br(temp)]
+    decompressed_size: u32,
+    #[br(temp)]
+    compressed_size: u32,
+    #[br(calc = compressed_size != 0)]
+    pub compress: bool,
+    pub class_name: Name,
+    pub name: Name,
+    #[br(calc = (link_header_size + decompressed_size) as usize)]
+    pub size: usize,
+    #[br(temp, pad_after = link_header_size + if compress { compressed_size } else { decompressed_size })]
+    _body: (),
+}
+
 impl Object {
     pub fn dump_resource<W: Write + Seek>(
         resource: &Resource,
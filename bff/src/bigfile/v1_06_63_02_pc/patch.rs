@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use binrw::{args, BinRead, BinWrite, Endian};
+
+use super::header::Header;
+use super::object::{Object, ObjectHeader};
+use super::pool::PoolHeader;
+use super::{block_checksum, write_block_objects};
+use crate::bigfile::resource::Resource;
+use crate::error::{ObjectNotFoundError, PooledObjectPatchError};
+use crate::helpers::{check_count, write_align_to};
+use crate::names::Name;
+use crate::traits::SetLen;
+use crate::BffResult;
+
+pub struct BlockPatch {
+    pub index: usize,
+    pub offset: u64,
+    pub padded_size: u64,
+    pub data: Vec<u8>,
+    pub data_size: u64,
+    pub calculated_working_buffer_offset: u64,
+}
+
+impl BlockPatch {
+    pub fn checksum(&self) -> i32 {
+        block_checksum(&self.data[..self.data_size as usize])
+    }
+}
+
+// Find the block holding the resource and encode it again with the resource replaced. Blocks start
+// at the first sector after the header and are laid out back to back.
+pub fn patch_block<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    blocks: impl IntoIterator<Item = (u32, u64)>,
+    resource: &Resource,
+    pooled: &HashSet<Name>,
+) -> BffResult<BlockPatch> {
+    if pooled.contains(&resource.name) {
+        return Err(PooledObjectPatchError::new(resource.name).into());
+    }
+
+    let mut offset = 2048u64;
+    for (index, (object_count, padded_size)) in blocks.into_iter().enumerate() {
+        reader.seek(SeekFrom::Start(offset))?;
+        let object_count = check_count(reader, object_count as usize)?;
+        let mut found = false;
+        for _ in 0..object_count {
+            found |= ObjectHeader::read_options(reader, endian, ())?.name == resource.name;
+        }
+
+        if found {
+            reader.seek(SeekFrom::Start(offset))?;
+            let objects =
+                Vec::<Object>::read_options(reader, endian, args! { count: object_count })?
+                    .into_iter()
+                    .map(|object| match object.name == resource.name {
+                        // Keep the compression of the object being replaced
+                        true => Resource {
+                            compress: object.compress,
+                            ..resource.clone()
+                        },
+                        false => object.into(),
+                    })
+                    .collect::<Vec<Resource>>();
+
+            let mut writer = Cursor::new(Vec::new());
+            let calculated_working_buffer_offset = write_block_objects(
+                &mut writer,
+                endian,
+                index,
+                objects
+                    .iter()
+                    .map(|object| (object, pooled.contains(&object.name))),
+            )?;
+            let data_size = writer.stream_position()?;
+            write_align_to(&mut writer, 2048, 0x00)?;
+
+            return Ok(BlockPatch {
+                index,
+                offset,
+                padded_size,
+                data: writer.into_inner(),
+                data_size,
+                calculated_working_buffer_offset: calculated_working_buffer_offset as u64,
+            });
+        }
+
+        offset += padded_size;
+    }
+
+    Err(ObjectNotFoundError::new(resource.name).into())
+}
+
+// Write the block over the old one. If the size changed everything after it is moved and the
+// stream is cut to its new end. The stream is left at the end of the BigFile and the returned value
+// is the number of bytes the rest of it moved by.
+pub fn replace_block<S: Read + Write + Seek + SetLen>(
+    stream: &mut S,
+    patch: &BlockPatch,
+) -> BffResult<i64> {
+    let delta = patch.data.len() as i64 - patch.padded_size as i64;
+
+    let mut tail = Vec::new();
+    if delta != 0 {
+        stream.seek(SeekFrom::Start(patch.offset + patch.padded_size))?;
+        stream.read_to_end(&mut tail)?;
+    }
+
+    stream.seek(SeekFrom::Start(patch.offset))?;
+    stream.write_all(&patch.data)?;
+
+    if delta == 0 {
+        stream.seek(SeekFrom::End(0))?;
+    } else {
+        stream.write_all(&tail)?;
+        let end = stream.stream_position()?;
+        stream.set_len(end)?;
+    }
+
+    Ok(delta)
+}
+
+pub fn patch<S: Read + Write + Seek + SetLen>(
+    stream: &mut S,
+    endian: Endian,
+    resource: &Resource,
+) -> BffResult<u64> {
+    let begin = stream.stream_position()?;
+    let mut header = Header::read_options(stream, endian, ())?;
+
+    let pool_header = match header.pool_offset {
+        Some(pool_offset) => {
+            stream.seek(SeekFrom::Start(pool_offset as u64))?;
+            Some(PoolHeader::read_options(stream, endian, ())?)
+        }
+        None => None,
+    };
+    let pooled = pool_header
+        .as_ref()
+        .map_or_else(HashSet::new, |pool_header| {
+            pool_header
+                .object_descriptions
+                .iter()
+                .map(|object_description| object_description.name)
+                .collect()
+        });
+
+    let patch = patch_block(
+        stream,
+        endian,
+        header
+            .block_descriptions
+            .iter()
+            .map(|block| (block.object_count, block.padded_size as u64)),
+        resource,
+        &pooled,
+    )?;
+    let delta = replace_block(stream, &patch)?;
+    let end = stream.stream_position()?;
+
+    let block = &mut header.block_descriptions[patch.index];
+    header.block_sector_padding_size = header.block_sector_padding_size
+        + (patch.data.len() as u64 - patch.data_size) as u32
+        - (block.padded_size - block.data_size);
+    block.padded_size = patch.data.len() as u32;
+    block.data_size = patch.data_size as u32;
+    block.working_buffer_offset = block
+        .working_buffer_offset
+        .max(patch.calculated_working_buffer_offset as u32);
+    block.checksum = block.checksum.map(|_| patch.checksum());
+
+    (
+        header.block_working_buffer_capacity_even,
+        header.block_working_buffer_capacity_odd,
+    ) = header
+        .block_descriptions
+        .iter()
+        .enumerate()
+        .fold((0, 0), |(even, odd), (i, block)| {
+            let capacity = block.padded_size + block.working_buffer_offset;
+            match i % 2 {
+                0 => (even.max(capacity), odd),
+                _ => (even, odd.max(capacity)),
+            }
+        });
+    header.padded_size = header
+        .block_descriptions
+        .iter()
+        .map(|block| block.padded_size)
+        .sum();
+    header.file_size = end as u32;
+
+    if let (Some(pool_offset), Some(mut pool_header)) = (header.pool_offset, pool_header) {
+        let pool_offset = (pool_offset as i64 + delta) as u32;
+        let chunk_delta = (delta / 2048) as i32;
+        for reference_record in pool_header.reference_records.iter_mut() {
+            reference_record.start_chunk_index = reference_record
+                .start_chunk_index
+                .wrapping_add_signed(chunk_delta);
+            reference_record.end_chunk_index = reference_record
+                .end_chunk_index
+                .wrapping_add_signed(chunk_delta);
+        }
+        stream.seek(SeekFrom::Start(pool_offset as u64))?;
+        pool_header.write_options(stream, endian, ())?;
+        header.pool_offset = Some(pool_offset);
+    }
+
+    stream.seek(SeekFrom::Start(begin))?;
+    header.write_options(stream, endian, ())?;
+
+    stream.seek(SeekFrom::Start(0x7C0))?;
+    stream.write_all(&[0xFF; 0x40])?;
+
+    Ok(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::bigfile::platforms::Platform;
+    use crate::bigfile::testing::{bigfile, block, name, resource};
+    use crate::bigfile::BigFile;
+    use crate::names::NameContext;
+
+    // Patch the object of the first of two blocks, read the BigFile back and return its length
+    // before and after
+    fn patch_first_block(old_size: usize, new_size: usize) -> (u64, u64) {
+        let [a, b] = [name("a"), name("b")];
+        let original = bigfile(
+            vec![block(&[a]), block(&[b])],
+            vec![
+                resource(a, "Mesh_Z", &vec![1; old_size]),
+                resource(b, "Mesh_Z", &[2; 16]),
+            ],
+        );
+        let mut stream = Cursor::new(Vec::new());
+        original.write(&mut stream, None, None).unwrap();
+        let original_len = stream.get_ref().len() as u64;
+
+        let replacement = resource(a, "Mesh_Z", &vec![3; new_size]);
+        stream.set_position(0);
+        let len = BigFile::patch_platform(
+            &mut stream,
+            Platform::PC,
+            &original.name_context,
+            &replacement,
+        )
+        .unwrap();
+
+        let data = stream.into_inner();
+        assert_eq!(len, data.len() as u64);
+        let patched = BigFile::read_platform(
+            &mut Cursor::new(data),
+            Platform::PC,
+            &NameContext::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(patched.objects[&a], replacement);
+        assert_eq!(patched.objects[&b], original.objects[&b]);
+
+        (original_len, len)
+    }
+
+    #[test]
+    fn shrinking_block_cuts_the_bigfile() {
+        let (original_len, len) = patch_first_block(5000, 16);
+        assert!(len < original_len);
+    }
+
+    #[test]
+    fn growing_block_moves_the_rest() {
+        let (original_len, len) = patch_first_block(16, 5000);
+        assert!(len > original_len);
+    }
+
+    #[test]
+    fn same_size_block_keeps_the_rest() {
+        let (original_len, len) = patch_first_block(16, 32);
+        assert_eq!(len, original_len);
+    }
+}
@@ -1,9 +1,17 @@
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
 
 use binrw::{binrw, BinRead, BinWrite};
 use serde::Serialize;
 
 use super::object::PoolObject;
+use crate::bigfile::manifest::{
+    ManifestBlock,
+    ManifestPool,
+    ManifestPoolObjectEntry,
+    ManifestPoolReferenceRecord,
+};
+use crate::error::{ArrayLengthMismatchError, PoolFlagMismatchError};
 use crate::helpers::{calculated_padded, DynArray};
 use crate::names::Name;
 
@@ -73,23 +81,27 @@ fn unzip_object_description_soa(
 
 fn zip_object_description_soa(
     object_description_soa: ObjectDescriptionSOA,
-) -> Vec<ObjectDescription> {
-    assert_eq!(
-        object_description_soa.names.len(),
-        object_description_soa.reference_counts.len()
-    );
-    assert_eq!(
-        object_description_soa.names.len(),
-        object_description_soa.padded_sizes.len()
-    );
-    assert_eq!(
-        object_description_soa.names.len(),
-        object_description_soa.reference_records_indices.len()
-    );
-
-    let mut result = Vec::with_capacity(object_description_soa.names.len());
-
-    for i in 0..object_description_soa.names.len() {
+) -> Result<Vec<ObjectDescription>, ArrayLengthMismatchError> {
+    let expected = object_description_soa.names.len();
+    for (array, found) in [
+        (
+            "reference_counts",
+            object_description_soa.reference_counts.len(),
+        ),
+        ("padded_sizes", object_description_soa.padded_sizes.len()),
+        (
+            "reference_records_indices",
+            object_description_soa.reference_records_indices.len(),
+        ),
+    ] {
+        if found != expected {
+            return Err(ArrayLengthMismatchError::new(array, expected, found));
+        }
+    }
+
+    let mut result = Vec::with_capacity(expected);
+
+    for i in 0..expected {
         result.push(ObjectDescription {
             name: object_description_soa.names[i],
             reference_count: object_description_soa.reference_counts[i],
@@ -98,7 +110,7 @@ fn zip_object_description_soa(
         })
     }
 
-    result
+    Ok(result)
 }
 
 #[binrw]
@@ -115,7 +127,7 @@ pub struct PoolHeader {
     #[serde(skip)]
     pub objects_names_count_sum: u32,
     pub object_descriptions_indices: DynArray<u32>,
-    #[br(map = zip_object_description_soa)]
+    #[br(try_map = zip_object_description_soa)]
     #[bw(map = unzip_object_description_soa)]
     pub object_descriptions: Vec<ObjectDescription>,
     pub reference_records: DynArray<ReferenceRecord>,
@@ -154,3 +166,229 @@ pub struct Pool {
     #[br(count = header.object_descriptions_indices.len())]
     pub objects: Vec<PoolObject>,
 }
+
+// Each block referencing pooled objects gets a reference record listing them in block order. An
+// object entry points at the first record referencing it.
+pub fn generate_manifest_pool(blocks: &[ManifestBlock]) -> Option<ManifestPool> {
+    let mut object_entry_indices = Vec::new();
+    let mut object_entries = Vec::new();
+    let mut reference_records = Vec::new();
+    let mut entry_indices: HashMap<Name, u32> = HashMap::new();
+
+    for block in blocks {
+        let object_entries_starting_index = object_entry_indices.len() as u32;
+
+        for object in block
+            .objects
+            .iter()
+            .filter(|object| object.pooled == Some(true))
+        {
+            let entry_index = *entry_indices.entry(object.name).or_insert_with(|| {
+                object_entries.push(ManifestPoolObjectEntry {
+                    name: object.name,
+                    reference_record_index: reference_records.len() as u32,
+                });
+                object_entries.len() as u32 - 1
+            });
+            object_entry_indices.push(entry_index);
+        }
+
+        let object_entries_count =
+            object_entry_indices.len() as u32 - object_entries_starting_index;
+        if object_entries_count != 0 {
+            reference_records.push(ManifestPoolReferenceRecord {
+                object_entries_starting_index,
+                object_entries_count: object_entries_count as u16,
+            });
+        }
+    }
+
+    (!object_entries.is_empty()).then_some(ManifestPool {
+        object_entry_indices,
+        object_entries,
+        reference_records,
+    })
+}
+
+pub fn mark_pooled_objects(blocks: &mut [ManifestBlock], pool: &ManifestPool) {
+    let pooled = pool
+        .object_entries
+        .iter()
+        .map(|entry| entry.name)
+        .collect::<HashSet<_>>();
+
+    for object in blocks.iter_mut().flat_map(|block| block.objects.iter_mut()) {
+        if pooled.contains(&object.name) {
+            object.pooled = Some(true);
+        }
+    }
+}
+
+// An explicit pool manifest is kept as is so BigFiles are written back identically, the objects
+// whose pooled flag is given must agree with it
+pub fn check_pooled_flags(
+    blocks: &[ManifestBlock],
+    pool: &ManifestPool,
+) -> Result<(), PoolFlagMismatchError> {
+    let pooled = pool
+        .object_entries
+        .iter()
+        .map(|entry| entry.name)
+        .collect::<HashSet<_>>();
+
+    for object in blocks.iter().flat_map(|block| block.objects.iter()) {
+        if let Some(flag) = object.pooled {
+            if flag != pooled.contains(&object.name) {
+                return Err(PoolFlagMismatchError::new(object.name));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use binrw::Endian;
+
+    use super::*;
+    use crate::bigfile::manifest::ManifestObject;
+    use crate::bigfile::testing;
+    use crate::bigfile::v1_06_63_02_pc::manifest_pool;
+    use crate::error::Error;
+    use crate::names::NameType;
+
+    fn block(names: &[(Name, bool)]) -> ManifestBlock {
+        ManifestBlock {
+            offset: None,
+            checksum: None,
+            compressed: None,
+            objects: names
+                .iter()
+                .map(|(name, pooled)| ManifestObject {
+                    name: *name,
+                    compress: None,
+                    pooled: pooled.then_some(true),
+                    external: None,
+                    unknowns: None,
+                })
+                .collect(),
+            resource_counts: None,
+            data: None,
+            unknowns: None,
+        }
+    }
+
+    fn reference_record(objects_name_starting_index: u32, count: u16) -> ReferenceRecord {
+        ReferenceRecord {
+            objects_name_starting_index,
+            objects_name_count: count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn generated_pool_matches_the_written_pool() {
+        let [a, b, c, d] = [b"a", b"b", b"c", b"d"].map(|bytes| NameType::Asobo32.hash(bytes));
+
+        // The second block shares a with the first, the third references nothing pooled and the
+        // fourth shares c with the second
+        let header = PoolHeader {
+            objects_names_count_sum: 5,
+            object_descriptions_indices: vec![0, 1, 0, 2, 2].into(),
+            object_descriptions: [(a, 2, 0), (b, 1, 0), (c, 2, 1)]
+                .into_iter()
+                .map(
+                    |(name, reference_count, reference_records_index)| ObjectDescription {
+                        name,
+                        reference_count,
+                        padded_size: 1,
+                        reference_records_index,
+                    },
+                )
+                .collect(),
+            reference_records: vec![
+                reference_record(0, 2),
+                reference_record(2, 2),
+                reference_record(4, 1),
+            ]
+            .into(),
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        header
+            .write_options(&mut cursor, Endian::Little, ())
+            .unwrap();
+        cursor.set_position(0);
+        let read =
+            manifest_pool(PoolHeader::read_options(&mut cursor, Endian::Little, ()).unwrap());
+
+        let blocks = [
+            block(&[(a, true), (d, false), (b, true)]),
+            block(&[(a, true), (c, true)]),
+            block(&[(d, false)]),
+            block(&[(c, true)]),
+        ];
+
+        assert_eq!(generate_manifest_pool(&blocks), Some(read));
+    }
+
+    #[test]
+    fn unflagged_objects_generate_no_pool() {
+        let a = NameType::Asobo32.hash(b"a");
+        assert_eq!(generate_manifest_pool(&[block(&[(a, false)])]), None);
+    }
+
+    #[test]
+    fn pooled_flags_must_agree_with_the_pool() {
+        let [a, b] = [b"a", b"b"].map(|bytes| NameType::Asobo32.hash(bytes));
+        let pool = generate_manifest_pool(&[block(&[(a, true)])]).unwrap();
+
+        let mut blocks = [block(&[(a, true), (b, false)])];
+        assert!(check_pooled_flags(&blocks, &pool).is_ok());
+
+        // Objects without a flag follow the pool manifest
+        blocks[0].objects[0].pooled = None;
+        assert!(check_pooled_flags(&blocks, &pool).is_ok());
+
+        blocks[0].objects[1].pooled = Some(true);
+        assert!(check_pooled_flags(&blocks, &pool).is_err());
+
+        blocks[0].objects[1].pooled = None;
+        blocks[0].objects[0].pooled = Some(false);
+        assert!(check_pooled_flags(&blocks, &pool).is_err());
+    }
+
+    #[test]
+    fn hand_edited_pool_manifests_are_errors() {
+        let a = testing::name("a");
+        let write = |edit: fn(&mut ManifestPool)| {
+            let mut bigfile = testing::bigfile(
+                vec![testing::block(&[a])],
+                vec![testing::resource(a, "Mesh_Z", &[1; 16])],
+            );
+            let mut pool = generate_manifest_pool(&[block(&[(a, true)])]).unwrap();
+            edit(&mut pool);
+            bigfile.manifest.pool = Some(pool);
+            bigfile.write(&mut Cursor::new(Vec::new()), None, None)
+        };
+
+        assert!(write(|_| ()).is_ok());
+        assert!(matches!(
+            write(|pool| pool.object_entry_indices[0] = 1),
+            Err(Error::PoolIndex(_))
+        ));
+        assert!(matches!(
+            write(|pool| pool.reference_records[0].object_entries_count = 2),
+            Err(Error::PoolIndex(_))
+        ));
+        assert!(matches!(
+            write(|pool| pool.object_entries.push(ManifestPoolObjectEntry {
+                name: testing::name("b"),
+                reference_record_index: 0,
+            })),
+            Err(Error::UnreferencedPoolObject(_))
+        ));
+    }
+}
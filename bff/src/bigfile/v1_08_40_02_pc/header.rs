@@ -3,6 +3,7 @@ use serde::Serialize;
 
 use crate::bigfile::v1_06_63_02_pc::header::BlockDescription;
 use crate::bigfile::versions::VersionTriple;
+use crate::helpers::checked_vec_parser;
 
 #[binrw]
 #[derive(Serialize, Debug)]
@@ -15,7 +16,7 @@ pub struct Header {
     pub total_padded_block_size: u32,
     pub version_triple: VersionTriple,
     #[serde(skip)]
-    #[br(count = block_count)]
+    #[br(parse_with = checked_vec_parser, args(block_count as usize))]
     pub block_descriptions: Vec<BlockDescription>,
     #[br(ignore)]
     pub tag: Option<Vec<u8>>,
@@ -1,78 +1,113 @@
-pub mod block;
 pub mod header;
 pub mod object;
 
+use std::borrow::Cow;
 use std::cmp::max;
 use std::collections::HashMap;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
-use binrw::{BinRead, BinResult, BinWrite};
-use block::Block;
+use binrw::{BinRead, BinWrite};
 use header::*;
-use object::Object;
+use object::{CompressedObject, Object};
 
+use crate::bigfile::compression::record_object;
+use crate::bigfile::lazy::{BigFileIndex, IndexedObject};
 use crate::bigfile::manifest::*;
 use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::{report, Progress};
 use crate::bigfile::resource::Resource;
 use crate::bigfile::resource::ResourceData::{Data, SplitData};
+use crate::bigfile::salvage::{recover, FailedRegion, SalvagedBigFile};
 use crate::bigfile::v1_06_63_02_pc::header::BlockDescription;
+use crate::bigfile::v1_06_63_02_pc::{
+    block_checksum,
+    block_objects_index_parser,
+    block_objects_parser,
+    verify_blocks,
+    walk_blocks,
+};
+use crate::bigfile::verify::ChecksumMismatch;
 use crate::bigfile::versions::{Version, VersionXple};
 use crate::bigfile::BigFile;
-use crate::helpers::{calculated_padded, write_align_to};
-use crate::lz::lzrs_compress_data_with_header_writer_internal;
+use crate::helpers::{calculated_padded, expect_end, par_map, write_align_to};
+use crate::lz::lzrs_compress_data_with_header;
 use crate::names::NameType::Asobo32;
-use crate::names::{Name, NameType};
+use crate::names::{Name, NameContext, NameType};
 use crate::traits::BigFileIo;
 use crate::{BffResult, Endian};
 
-#[binrw::parser(reader, endian)]
-fn blocks_parser(
+fn blocks_parser<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
     block_descriptions: Vec<BlockDescription>,
     objects: &mut HashMap<Name, Resource>,
-) -> BinResult<Vec<ManifestBlock>> {
-    let mut blocks: Vec<ManifestBlock> = Vec::with_capacity(block_descriptions.len());
-
-    for block_description in block_descriptions {
-        let block = Block::read_options(reader, endian, (&block_description,))?;
-        let mut block_objects = Vec::with_capacity(block.objects.len());
-        for object in block.objects.into_iter() {
-            block_objects.push(ManifestObject {
-                name: object.name,
-                compress: Some(object.compress),
-            });
-
-            objects.insert(object.name, object.into());
-        }
-
-        blocks.push(ManifestBlock {
-            offset: Some(block_description.working_buffer_offset as u64),
-            checksum: block_description.checksum,
-            compressed: None,
-            objects: block_objects,
-        });
-    }
+    failures: &mut Option<Vec<FailedRegion>>,
+) -> BffResult<Vec<ManifestBlock>> {
+    walk_blocks(
+        reader,
+        block_descriptions,
+        failures,
+        |reader, block_index, object_count, block_objects| {
+            block_objects_parser::<CompressedObject, _>(
+                reader,
+                endian,
+                block_index,
+                object_count,
+                objects,
+                block_objects,
+            )
+        },
+    )
+}
 
-    Ok(blocks)
+fn blocks_index_parser<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    block_descriptions: Vec<BlockDescription>,
+    objects: &mut HashMap<Name, IndexedObject>,
+) -> BffResult<Vec<ManifestBlock>> {
+    walk_blocks(
+        reader,
+        block_descriptions,
+        &mut None,
+        |reader, _, object_count, block_objects| {
+            block_objects_index_parser::<CompressedObject, _>(
+                reader,
+                endian,
+                object_count,
+                objects,
+                block_objects,
+            )
+        },
+    )
 }
 
 pub struct BigFileV1_08_40_02PC;
 
-impl BigFileIo for BigFileV1_08_40_02PC {
-    fn read<R: Read + Seek>(
+impl BigFileV1_08_40_02PC {
+    fn read_with_failures<R: Read + Seek>(
         reader: &mut R,
         version: Version,
         platform: Platform,
+        failures: &mut Option<Vec<FailedRegion>>,
     ) -> BffResult<BigFile> {
         let endian = platform.into();
         let header = Header::read_options(reader, endian, ())?;
 
         let mut objects = HashMap::new();
 
-        let blocks = blocks_parser(reader, endian, (header.block_descriptions, &mut objects))?;
+        let blocks = blocks_parser(
+            reader,
+            endian,
+            header.block_descriptions,
+            &mut objects,
+            failures,
+        )?;
 
-        let pos = reader.stream_position().unwrap();
-        let len = reader.seek(SeekFrom::End(0)).unwrap();
-        assert_eq!(pos, len);
+        let offset = reader.stream_position()?;
+        let result = expect_end(reader);
+        let len = reader.stream_position()?;
+        recover(failures, None, offset, len, result)?;
 
         Ok(BigFile {
             manifest: Manifest {
@@ -84,8 +119,64 @@ impl BigFileIo for BigFileV1_08_40_02PC {
                 incredi_builder_string: None,
                 blocks,
                 pool: None,
+                header_unknowns: None,
+                compression: None,
+            },
+            objects,
+            name_context: NameContext::current(),
+        })
+    }
+}
+
+impl BigFileIo for BigFileV1_08_40_02PC {
+    fn read<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFile> {
+        Self::read_with_failures(reader, version, platform, &mut None)
+    }
+
+    fn read_salvage<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<SalvagedBigFile> {
+        let mut failures = Some(Vec::new());
+        let bigfile = Self::read_with_failures(reader, version, platform, &mut failures)?;
+        Ok(SalvagedBigFile {
+            bigfile,
+            failures: failures.unwrap_or_default(),
+        })
+    }
+
+    fn read_index<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFileIndex> {
+        let endian = platform.into();
+        let header = Header::read_options(reader, endian, ())?;
+
+        let mut objects = HashMap::new();
+
+        let blocks = blocks_index_parser(reader, endian, header.block_descriptions, &mut objects)?;
+
+        Ok(BigFileIndex {
+            manifest: Manifest {
+                version,
+                version_xple: Some(header.version_triple.into()),
+                platform,
+                bigfile_type: None,
+                pool_manifest_unused: None,
+                incredi_builder_string: None,
+                blocks,
+                pool: None,
+                header_unknowns: None,
+                compression: None,
             },
             objects,
+            name_context: NameContext::current(),
         })
     }
 
@@ -108,63 +199,64 @@ impl BigFileIo for BigFileV1_08_40_02PC {
         let mut block_descriptions = Vec::with_capacity(bigfile.manifest.blocks.len());
 
         for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
-            let block_begin = writer.stream_position()?;
+            report(Progress::Block {
+                index: i,
+                count: bigfile.manifest.blocks.len(),
+            })?;
 
-            let mut calculated_working_buffer_offset = 0usize;
+            let mut block_writer = Cursor::new(Vec::new());
+            let block_begin = block_writer.stream_position()?;
 
-            for object in block.objects.iter() {
-                let resource = bigfile.objects.get(&object.name).unwrap();
-                let begin_resource = writer.stream_position()?;
-                match (&resource.data, resource.compress) {
-                    (Data(data), true) => {
-                        let begin_header = writer.stream_position()?;
-                        writer.seek(SeekFrom::Current(16))?;
-                        let begin_data = writer.stream_position()?;
-                        lzrs_compress_data_with_header_writer_internal(data, writer, endian, ())?;
-                        let end_data = writer.stream_position()?;
-                        writer.seek(SeekFrom::Start(begin_header))?;
-                        (data.len() as u32).write_options(writer, endian, ())?;
-                        ((end_data - begin_data) as u32).write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.seek(SeekFrom::Start(end_data))?;
+            let mut calculated_working_buffer_offset = 0usize;
 
-                        let needed_working_buffer_offset =
-                            if data.len() > (begin_resource - block_begin) as usize {
-                                data.len()
-                            } else {
-                                0
-                            };
+            // Compress the objects in parallel, they are then written in order
+            let resources = block
+                .objects
+                .iter()
+                .map(|object| bigfile.object(&object.name))
+                .collect::<Result<Vec<_>, _>>()?;
+            let compressed_data = par_map(resources.clone(), |resource| {
+                if !resource.compress {
+                    return Ok(None);
+                }
+                let data = match &resource.data {
+                    Data(data) => Cow::Borrowed(data.as_slice()),
+                    SplitData { link_header, body } => {
+                        Cow::Owned([link_header.as_slice(), body.as_slice()].concat())
+                    }
+                };
+                lzrs_compress_data_with_header(&data, endian)
+                    .map(|compressed_data| Some((data.len(), compressed_data)))
+            });
 
-                        calculated_working_buffer_offset = max(
-                            needed_working_buffer_offset,
-                            calculated_working_buffer_offset,
+            for (resource, compressed_data) in resources.into_iter().zip(compressed_data) {
+                let begin_resource = block_writer.stream_position()?;
+                match (&resource.data, compressed_data?) {
+                    (_, Some((data_len, compressed_data))) => {
+                        (data_len as u32).write_options(&mut block_writer, endian, ())?;
+                        (compressed_data.len() as u32).write_options(
+                            &mut block_writer,
+                            endian,
+                            (),
+                        )?;
+                        resource
+                            .class_name
+                            .write_options(&mut block_writer, endian, ())?;
+                        resource.name.write_options(&mut block_writer, endian, ())?;
+                        block_writer.write_all(&compressed_data)?;
+                        record_object(
+                            Some(i),
+                            resource.class_name,
+                            data_len,
+                            compressed_data.len(),
                         );
-                    }
-                    (Data(data), false) => {
-                        (data.len() as u32).write_options(writer, endian, ())?;
-                        0u32.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        data.write_options(writer, endian, ())?;
-                    }
-                    (SplitData { link_header, body }, true) => {
-                        let data = [link_header.as_slice(), body.as_slice()].concat();
-                        let begin_header = writer.stream_position()?;
-                        writer.seek(SeekFrom::Current(16))?;
-                        let begin_data = writer.stream_position()?;
-                        lzrs_compress_data_with_header_writer_internal(&data, writer, endian, ())?;
-                        let end_data = writer.stream_position()?;
-                        writer.seek(SeekFrom::Start(begin_header))?;
-                        (data.len() as u32).write_options(writer, endian, ())?;
-                        ((end_data - begin_data) as u32).write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.seek(SeekFrom::Start(end_data))?;
+                        report(Progress::Object {
+                            name: resource.name,
+                        })?;
 
                         let needed_working_buffer_offset =
-                            if data.len() > (begin_resource - block_begin) as usize {
-                                data.len()
+                            if data_len > (begin_resource - block_begin) as usize {
+                                data_len
                             } else {
                                 0
                             };
@@ -174,23 +266,48 @@ impl BigFileIo for BigFileV1_08_40_02PC {
                             calculated_working_buffer_offset,
                         );
                     }
-                    (SplitData { link_header, body }, false) => {
+                    (Data(data), None) => {
+                        (data.len() as u32).write_options(&mut block_writer, endian, ())?;
+                        0u32.write_options(&mut block_writer, endian, ())?;
+                        resource
+                            .class_name
+                            .write_options(&mut block_writer, endian, ())?;
+                        resource.name.write_options(&mut block_writer, endian, ())?;
+                        data.write_options(&mut block_writer, endian, ())?;
+                        record_object(Some(i), resource.class_name, data.len(), data.len());
+                        report(Progress::Object {
+                            name: resource.name,
+                        })?;
+                    }
+                    (SplitData { link_header, body }, None) => {
                         ((link_header.len() + body.len()) as u32).write_options(
-                            writer,
+                            &mut block_writer,
                             endian,
                             (),
                         )?;
-                        0u32.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        link_header.write_options(writer, endian, ())?;
-                        body.write_options(writer, endian, ())?;
+                        0u32.write_options(&mut block_writer, endian, ())?;
+                        resource
+                            .class_name
+                            .write_options(&mut block_writer, endian, ())?;
+                        resource.name.write_options(&mut block_writer, endian, ())?;
+                        link_header.write_options(&mut block_writer, endian, ())?;
+                        body.write_options(&mut block_writer, endian, ())?;
+                        record_object(
+                            Some(i),
+                            resource.class_name,
+                            resource.size(),
+                            resource.size(),
+                        );
+                        report(Progress::Object {
+                            name: resource.name,
+                        })?;
                     }
                 }
             }
 
-            let block_end = writer.stream_position()?;
-            let data_size = (block_end - block_begin) as u32;
+            let block_data = block_writer.into_inner();
+            writer.write_all(&block_data)?;
+            let data_size = block_data.len() as u32;
             let padding = write_align_to(writer, 2048, 0x00)?;
             let padded_size = data_size + padding as u32;
 
@@ -219,8 +336,7 @@ impl BigFileIo for BigFileV1_08_40_02PC {
                 data_size,
                 working_buffer_offset,
                 first_object_name: block.objects.first().map(|r| r.name).unwrap_or_default(),
-                // TODO: Calculate checksum using Asobo Alternate on the unpadded block while writing
-                checksum: block.checksum,
+                checksum: Some(block_checksum(&block_data)),
             });
         }
 
@@ -244,6 +360,15 @@ impl BigFileIo for BigFileV1_08_40_02PC {
         Ok(())
     }
 
+    fn verify<R: Read + Seek>(
+        reader: &mut R,
+        _version: Version,
+        platform: Platform,
+    ) -> BffResult<Vec<ChecksumMismatch>> {
+        let header = Header::read_options(reader, platform.into(), ())?;
+        verify_blocks(reader, &header.block_descriptions)
+    }
+
     const NAME_TYPE: NameType = Asobo32;
 
     type ResourceType = Object;
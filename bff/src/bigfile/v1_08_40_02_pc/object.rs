@@ -1,11 +1,13 @@
-use std::io::{Read, Seek, Write};
+use std::io::{Cursor, Read, Seek, Write};
 
 use binrw::{binread, BinRead, BinResult, BinWrite, Endian};
 use serde::Serialize;
 
 use crate::bigfile::resource::Resource;
 use crate::bigfile::resource::ResourceData::{Data, SplitData};
-use crate::bigfile::v1_06_63_02_pc::object::body_parser;
+use crate::bigfile::v1_06_63_02_pc;
+use crate::bigfile::v1_06_63_02_pc::object::{body_parser, CompressedBlockObject};
+use crate::helpers::checked_vec_parser;
 use crate::names::Name;
 
 #[binread]
@@ -24,6 +26,71 @@ pub struct Object {
     pub data: Vec<u8>,
 }
 
+// An object whose data is read but not yet decompressed, so the data of a block can be
+// decompressed in parallel once it has been read
+#[binread]
+#[derive(Debug)]
+pub struct CompressedObject {
+    decompressed_size: u32,
+    compressed_size: u32,
+    pub class_name: Name,
+    pub name: Name,
+    #[br(parse_with = checked_vec_parser, args(if compressed_size != 0 { compressed_size } else { decompressed_size } as usize))]
+    data: Vec<u8>,
+}
+
+impl CompressedObject {
+    pub fn decompress(self, endian: Endian) -> BinResult<Object> {
+        let data = body_parser(
+            &mut Cursor::new(self.data),
+            endian,
+            (self.decompressed_size, self.compressed_size),
+        )?;
+        Ok(Object {
+            compress: self.compressed_size != 0,
+            class_name: self.class_name,
+            name: self.name,
+            data,
+        })
+    }
+}
+
+impl CompressedBlockObject for CompressedObject {
+    type Header = ObjectHeader;
+
+    fn into_resource(self, endian: Endian) -> BinResult<Resource> {
+        Ok(self.decompress(endian)?.into())
+    }
+}
+
+#[binread]
+#[derive(Debug)]
+pub struct ObjectHeader {
+    #[br(temp)]
+    decompressed_size: u32,
+    #[br(temp)]
+    compressed_size: u32,
+    #[br(calc = compressed_size != 0)]
+    pub compress: bool,
+    pub class_name: Name,
+    pub name: Name,
+    #[br(calc = decompressed_size as usize)]
+    pub size: usize,
+    #[br(temp, pad_after = if compress { compressed_size } else { decompressed_size })]
+    _body: (),
+}
+
+impl From<ObjectHeader> for v1_06_63_02_pc::object::ObjectHeader {
+    fn from(header: ObjectHeader) -> Self {
+        Self {
+            compress: header.compress,
+            class_name: header.class_name,
+            name: header.name,
+            size: header.size,
+        }
+    }
+}
+
 impl Object {
     pub fn dump_resource<W: Write + Seek>(
         resource: &Resource,
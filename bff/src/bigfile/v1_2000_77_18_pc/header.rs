@@ -5,6 +5,7 @@ use serde::Serialize;
 
 use crate::bigfile::v1_06_63_02_pc::header::{BigFileType, BlockDescription};
 use crate::bigfile::versions::VersionOneple;
+use crate::helpers::checked_vec_parser;
 
 #[binrw]
 #[derive(Serialize, Debug)]
@@ -18,7 +19,7 @@ pub struct Header {
     pub block_working_buffer_capacity_odd: u32,
     pub total_padded_block_size: u32,
     #[serde(skip)]
-    #[br(count = block_count)]
+    #[br(parse_with = checked_vec_parser, args(block_count as usize))]
     pub block_descriptions: Vec<BlockDescription>,
     #[br(ignore)]
     pub tag: Option<Vec<u8>>,
@@ -2,44 +2,60 @@ pub mod header;
 
 use std::cmp::max;
 use std::collections::HashMap;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
 use binrw::{BinRead, BinWrite, Endian};
 use header::*;
 
+use crate::bigfile::lazy::BigFileIndex;
 use crate::bigfile::manifest::*;
 use crate::bigfile::platforms::Platform;
-use crate::bigfile::resource::ResourceData::SplitData;
-use crate::bigfile::v1_06_63_02_pc::blocks_parser;
+use crate::bigfile::progress::{report, Progress};
+use crate::bigfile::salvage::{recover, FailedRegion, SalvagedBigFile};
 use crate::bigfile::v1_06_63_02_pc::header::BlockDescription;
 use crate::bigfile::v1_06_63_02_pc::object::Object;
+use crate::bigfile::v1_06_63_02_pc::{
+    block_checksum,
+    blocks_index_parser,
+    blocks_parser,
+    verify_blocks,
+    write_block_objects,
+};
+use crate::bigfile::verify::ChecksumMismatch;
 use crate::bigfile::versions::{Version, VersionXple};
 use crate::bigfile::BigFile;
-use crate::helpers::{calculated_padded, write_align_to};
-use crate::lz::lzrs_compress_data_with_header_writer_internal;
-use crate::names::NameType;
+use crate::helpers::{calculated_padded, expect_end, write_align_to};
 use crate::names::NameType::Asobo32;
+use crate::names::{NameContext, NameType};
 use crate::traits::BigFileIo;
 use crate::BffResult;
 
 pub struct BigFileV1_2000_77_18PC;
 
-impl BigFileIo for BigFileV1_2000_77_18PC {
-    fn read<R: Read + Seek>(
+impl BigFileV1_2000_77_18PC {
+    fn read_with_failures<R: Read + Seek>(
         reader: &mut R,
         version: Version,
         platform: Platform,
+        failures: &mut Option<Vec<FailedRegion>>,
     ) -> BffResult<BigFile> {
         let endian = platform.into();
         let header = Header::read_options(reader, endian, ())?;
 
         let mut objects = HashMap::new();
 
-        let blocks = blocks_parser(reader, endian, (header.block_descriptions, &mut objects))?;
+        let blocks = blocks_parser(
+            reader,
+            endian,
+            header.block_descriptions,
+            &mut objects,
+            failures,
+        )?;
 
-        let pos = reader.stream_position().unwrap();
-        let len = reader.seek(SeekFrom::End(0)).unwrap();
-        assert_eq!(pos, len);
+        let offset = reader.stream_position()?;
+        let result = expect_end(reader);
+        let len = reader.stream_position()?;
+        recover(failures, None, offset, len, result)?;
 
         Ok(BigFile {
             manifest: Manifest {
@@ -51,8 +67,64 @@ impl BigFileIo for BigFileV1_2000_77_18PC {
                 incredi_builder_string: None,
                 blocks,
                 pool: None,
+                header_unknowns: None,
+                compression: None,
+            },
+            objects,
+            name_context: NameContext::current(),
+        })
+    }
+}
+
+impl BigFileIo for BigFileV1_2000_77_18PC {
+    fn read<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFile> {
+        Self::read_with_failures(reader, version, platform, &mut None)
+    }
+
+    fn read_salvage<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<SalvagedBigFile> {
+        let mut failures = Some(Vec::new());
+        let bigfile = Self::read_with_failures(reader, version, platform, &mut failures)?;
+        Ok(SalvagedBigFile {
+            bigfile,
+            failures: failures.unwrap_or_default(),
+        })
+    }
+
+    fn read_index<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFileIndex> {
+        let endian = platform.into();
+        let header = Header::read_options(reader, endian, ())?;
+
+        let mut objects = HashMap::new();
+
+        let blocks = blocks_index_parser(reader, endian, header.block_descriptions, &mut objects)?;
+
+        Ok(BigFileIndex {
+            manifest: Manifest {
+                version,
+                version_xple: Some(header.version_oneple.into()),
+                platform,
+                bigfile_type: Some(header.bigfile_type.into()),
+                pool_manifest_unused: None,
+                incredi_builder_string: None,
+                blocks,
+                pool: None,
+                header_unknowns: None,
+                compression: None,
             },
             objects,
+            name_context: NameContext::current(),
         })
     }
 
@@ -73,67 +145,27 @@ impl BigFileIo for BigFileV1_2000_77_18PC {
         let mut block_descriptions = Vec::with_capacity(bigfile.manifest.blocks.len());
 
         for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
-            let block_begin = writer.stream_position()?;
-
-            let mut calculated_working_buffer_offset = 0usize;
-
-            for object in block.objects.iter() {
-                let resource = bigfile.objects.get(&object.name).unwrap();
-                let begin_resource = writer.stream_position()?;
-                match (&resource.data, resource.compress) {
-                    (SplitData { link_header, body }, true) => {
-                        let begin_header = writer.stream_position()?;
-                        writer.seek(SeekFrom::Current(24))?;
-                        writer.write_all(link_header)?;
-                        let begin_body = writer.stream_position()?;
-                        lzrs_compress_data_with_header_writer_internal(body, writer, endian, ())?;
-                        let end_body = writer.stream_position()?;
-                        writer.seek(SeekFrom::Start(begin_header))?;
-                        let compressed_body_size = (end_body - begin_body) as u32;
-                        (link_header.len() as u32 + compressed_body_size).write_options(
-                            writer,
-                            endian,
-                            (),
-                        )?;
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        (body.len() as u32).write_options(writer, endian, ())?;
-                        compressed_body_size.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.seek(SeekFrom::Start(end_body))?;
-
-                        let needed_working_buffer_offset =
-                            if body.len() > (begin_resource - block_begin) as usize {
-                                body.len()
-                            } else {
-                                0
-                            };
-
-                        calculated_working_buffer_offset = max(
-                            needed_working_buffer_offset,
-                            calculated_working_buffer_offset,
-                        );
-                    }
-                    (SplitData { link_header, body }, false) => {
-                        (link_header.len() as u32 + body.len() as u32).write_options(
-                            writer,
-                            endian,
-                            (),
-                        )?;
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        (body.len() as u32).write_options(writer, endian, ())?;
-                        0u32.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.write_all(link_header)?;
-                        writer.write_all(body)?;
-                    }
-                    _ => todo!(),
-                }
-            }
-
-            let block_end = writer.stream_position()?;
-            let data_size = (block_end - block_begin) as u32;
+            report(Progress::Block {
+                index: i,
+                count: bigfile.manifest.blocks.len(),
+            })?;
+
+            let mut block_writer = Cursor::new(Vec::new());
+
+            let calculated_working_buffer_offset = write_block_objects(
+                &mut block_writer,
+                endian,
+                i,
+                block
+                    .objects
+                    .iter()
+                    .map(|object| Ok((bigfile.object(&object.name)?, false)))
+                    .collect::<BffResult<Vec<_>>>()?,
+            )?;
+
+            let block_data = block_writer.into_inner();
+            writer.write_all(&block_data)?;
+            let data_size = block_data.len() as u32;
             let padding = write_align_to(writer, 2048, 0x00)?;
             let padded_size = data_size + padding as u32;
 
@@ -164,8 +196,7 @@ impl BigFileIo for BigFileV1_2000_77_18PC {
                 data_size,
                 working_buffer_offset,
                 first_object_name: block.objects.first().map(|r| r.name).unwrap_or_default(),
-                // TODO: Calculate checksum using Asobo Alternate on the unpadded block while writing
-                checksum: block.checksum,
+                checksum: Some(block_checksum(&block_data)),
             });
         }
 
@@ -206,6 +237,15 @@ impl BigFileIo for BigFileV1_2000_77_18PC {
         Ok(())
     }
 
+    fn verify<R: Read + Seek>(
+        reader: &mut R,
+        _version: Version,
+        platform: Platform,
+    ) -> BffResult<Vec<ChecksumMismatch>> {
+        let header = Header::read_options(reader, platform.into(), ())?;
+        verify_blocks(reader, &header.block_descriptions)
+    }
+
     const NAME_TYPE: NameType = Asobo32;
 
     type ResourceType = Object;
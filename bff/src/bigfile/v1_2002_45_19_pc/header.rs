@@ -5,6 +5,7 @@ use serde::Serialize;
 
 use crate::bigfile::v1_06_63_02_pc::header::BigFileType;
 use crate::bigfile::versions::VersionOneple;
+use crate::helpers::checked_vec_parser;
 
 #[derive(Serialize, Debug, BinRead, BinWrite)]
 pub struct BlockDescription {
@@ -26,7 +27,7 @@ pub struct Header {
     pub block_working_buffer_capacity_odd: u64,
     pub total_padded_block_size: u64,
     #[serde(skip)]
-    #[br(count = block_count)]
+    #[br(parse_with = checked_vec_parser, args(block_count as usize))]
     pub block_descriptions: Vec<BlockDescription>,
     #[br(ignore)]
     pub tag: Option<Vec<u8>>,
@@ -1,88 +1,150 @@
-pub mod block;
 pub mod header;
 
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek, SeekFrom, Write};
 
-use binrw::{BinRead, BinResult, BinWrite, Endian};
-use block::*;
+use binrw::{BinRead, BinWrite, Endian};
 use header::*;
 
+use crate::bigfile::lazy::{BigFileIndex, IndexedObject};
 use crate::bigfile::manifest::*;
 use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::{report, report_objects, Progress};
 use crate::bigfile::resource::Resource;
-use crate::bigfile::resource::ResourceData::SplitData;
-use crate::bigfile::v1_06_63_02_pc::object::Object;
+use crate::bigfile::salvage::{recover, FailedRegion, SalvagedBigFile};
+use crate::bigfile::v1_06_63_02_pc::object::{CompressedObject, Object};
+use crate::bigfile::v1_06_63_02_pc::patch::{patch_block, replace_block};
+use crate::bigfile::v1_06_63_02_pc::{
+    block_objects_index_parser,
+    block_objects_parser,
+    write_block_objects,
+};
 use crate::bigfile::versions::{Version, VersionXple};
 use crate::bigfile::BigFile;
-use crate::helpers::{calculated_padded, write_align_to};
-use crate::lz::lzrs_compress_data_with_header_writer_internal;
+use crate::helpers::{calculated_padded, expect_end, write_align_to};
 use crate::names::NameType::Asobo32;
-use crate::names::{Name, NameType};
-use crate::traits::BigFileIo;
+use crate::names::{Name, NameContext, NameType};
+use crate::traits::{BigFileIo, SetLen};
 use crate::BffResult;
 
 pub struct BigFileV1_2002_45_19PC;
 
-#[binrw::parser(reader, endian)]
-pub fn blocks_parser(
+// The v1_06 walk for the 64-bit block descriptions of this version, which have no checksum
+fn walk_blocks<R: Read + Seek>(
+    reader: &mut R,
     block_descriptions: Vec<BlockDescription>,
-    objects: &mut HashMap<Name, Resource>,
-) -> BinResult<Vec<ManifestBlock>> {
-    let mut blocks: Vec<ManifestBlock> = Vec::with_capacity(block_descriptions.len());
-
-    for block_description in block_descriptions {
-        let block = Block::read_options(reader, endian, (&block_description,))?;
-        let mut block_objects = Vec::with_capacity(block.objects.len());
-        for object in block.objects.into_iter() {
-            block_objects.push(ManifestObject {
-                name: object.name,
-                compress: Some(object.compress),
-            });
-
-            objects.insert(
-                object.name,
-                Resource {
-                    class_name: object.class_name,
-                    name: object.name,
-                    link_name: None,
-                    compress: object.compress,
-                    data: SplitData {
-                        link_header: object.link_header,
-                        body: object.body,
-                    },
-                },
-            );
+    failures: &mut Option<Vec<FailedRegion>>,
+    mut read_objects: impl FnMut(&mut R, usize, u32, &mut Vec<ManifestObject>) -> BffResult<()>,
+) -> BffResult<Vec<ManifestBlock>> {
+    let block_count = block_descriptions.len();
+    let mut blocks: Vec<ManifestBlock> = Vec::with_capacity(block_count);
+
+    for (block_index, block_description) in block_descriptions.into_iter().enumerate() {
+        report(Progress::Block {
+            index: block_index,
+            count: block_count,
+        })?;
+
+        let block_offset = reader.stream_position()?;
+        let block_end = block_offset + block_description.padded_size;
+        let mut block_objects = Vec::new();
+        let result = read_objects(
+            reader,
+            block_index,
+            block_description.object_count,
+            &mut block_objects,
+        );
+        if recover(failures, Some(block_index), block_offset, block_end, result)?.is_none() {
+            reader.seek(SeekFrom::Start(block_end))?;
         }
 
+        report_objects(block_objects.iter().map(|object| object.name))?;
+
         blocks.push(ManifestBlock {
             offset: Some(block_description.working_buffer_offset),
             checksum: None,
             compressed: None,
             objects: block_objects,
+            resource_counts: None,
+            data: None,
+            unknowns: None,
         });
     }
 
     Ok(blocks)
 }
 
-impl BigFileIo for BigFileV1_2002_45_19PC {
-    fn read<R: Read + Seek>(
+pub fn blocks_parser<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    block_descriptions: Vec<BlockDescription>,
+    objects: &mut HashMap<Name, Resource>,
+    failures: &mut Option<Vec<FailedRegion>>,
+) -> BffResult<Vec<ManifestBlock>> {
+    walk_blocks(
+        reader,
+        block_descriptions,
+        failures,
+        |reader, block_index, object_count, block_objects| {
+            block_objects_parser::<CompressedObject, _>(
+                reader,
+                endian,
+                block_index,
+                object_count,
+                objects,
+                block_objects,
+            )
+        },
+    )
+}
+
+pub fn blocks_index_parser<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    block_descriptions: Vec<BlockDescription>,
+    objects: &mut HashMap<Name, IndexedObject>,
+) -> BffResult<Vec<ManifestBlock>> {
+    walk_blocks(
+        reader,
+        block_descriptions,
+        &mut None,
+        |reader, _, object_count, block_objects| {
+            block_objects_index_parser::<CompressedObject, _>(
+                reader,
+                endian,
+                object_count,
+                objects,
+                block_objects,
+            )
+        },
+    )
+}
+
+impl BigFileV1_2002_45_19PC {
+    fn read_with_failures<R: Read + Seek>(
         reader: &mut R,
         version: Version,
         platform: Platform,
+        failures: &mut Option<Vec<FailedRegion>>,
     ) -> BffResult<BigFile> {
         let endian = platform.into();
         let header = Header::read_options(reader, endian, ())?;
 
         let mut objects = HashMap::new();
 
-        let blocks = blocks_parser(reader, endian, (header.block_descriptions, &mut objects))?;
+        let blocks = blocks_parser(
+            reader,
+            endian,
+            header.block_descriptions,
+            &mut objects,
+            failures,
+        )?;
 
-        let pos = reader.stream_position().unwrap();
-        let len = reader.seek(SeekFrom::End(0)).unwrap();
-        assert_eq!(pos, len);
+        let offset = reader.stream_position()?;
+        let result = expect_end(reader);
+        let len = reader.stream_position()?;
+        recover(failures, None, offset, len, result)?;
 
         Ok(BigFile {
             manifest: Manifest {
@@ -94,8 +156,64 @@ impl BigFileIo for BigFileV1_2002_45_19PC {
                 incredi_builder_string: None,
                 blocks,
                 pool: None,
+                header_unknowns: None,
+                compression: None,
+            },
+            objects,
+            name_context: NameContext::current(),
+        })
+    }
+}
+
+impl BigFileIo for BigFileV1_2002_45_19PC {
+    fn read<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFile> {
+        Self::read_with_failures(reader, version, platform, &mut None)
+    }
+
+    fn read_salvage<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<SalvagedBigFile> {
+        let mut failures = Some(Vec::new());
+        let bigfile = Self::read_with_failures(reader, version, platform, &mut failures)?;
+        Ok(SalvagedBigFile {
+            bigfile,
+            failures: failures.unwrap_or_default(),
+        })
+    }
+
+    fn read_index<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFileIndex> {
+        let endian = platform.into();
+        let header = Header::read_options(reader, endian, ())?;
+
+        let mut objects = HashMap::new();
+
+        let blocks = blocks_index_parser(reader, endian, header.block_descriptions, &mut objects)?;
+
+        Ok(BigFileIndex {
+            manifest: Manifest {
+                version,
+                version_xple: Some(header.version_oneple.into()),
+                platform,
+                bigfile_type: Some(header.bigfile_type.into()),
+                pool_manifest_unused: None,
+                incredi_builder_string: None,
+                blocks,
+                pool: None,
+                header_unknowns: None,
+                compression: None,
             },
             objects,
+            name_context: NameContext::current(),
         })
     }
 
@@ -116,64 +234,23 @@ impl BigFileIo for BigFileV1_2002_45_19PC {
         let mut block_descriptions = Vec::with_capacity(bigfile.manifest.blocks.len());
 
         for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
+            report(Progress::Block {
+                index: i,
+                count: bigfile.manifest.blocks.len(),
+            })?;
+
             let block_begin = writer.stream_position()?;
 
-            let mut calculated_working_buffer_offset = 0usize;
-
-            for object in block.objects.iter() {
-                let resource = bigfile.objects.get(&object.name).unwrap();
-                let begin_resource = writer.stream_position()?;
-                match (&resource.data, resource.compress) {
-                    (SplitData { link_header, body }, true) => {
-                        let begin_header = writer.stream_position()?;
-                        writer.seek(SeekFrom::Current(24))?;
-                        writer.write_all(link_header)?;
-                        let begin_body = writer.stream_position()?;
-                        lzrs_compress_data_with_header_writer_internal(body, writer, endian, ())?;
-                        let end_body = writer.stream_position()?;
-                        writer.seek(SeekFrom::Start(begin_header))?;
-                        let compressed_body_size = (end_body - begin_body) as u32;
-                        (link_header.len() as u32 + compressed_body_size).write_options(
-                            writer,
-                            endian,
-                            (),
-                        )?;
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        (body.len() as u32).write_options(writer, endian, ())?;
-                        compressed_body_size.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.seek(SeekFrom::Start(end_body))?;
-
-                        let needed_working_buffer_offset =
-                            if body.len() > (begin_resource - block_begin) as usize {
-                                body.len()
-                            } else {
-                                0
-                            };
-
-                        calculated_working_buffer_offset = max(
-                            needed_working_buffer_offset,
-                            calculated_working_buffer_offset,
-                        );
-                    }
-                    (SplitData { link_header, body }, false) => {
-                        (link_header.len() as u32 + body.len() as u32).write_options(
-                            writer,
-                            endian,
-                            (),
-                        )?;
-                        (link_header.len() as u32).write_options(writer, endian, ())?;
-                        (body.len() as u32).write_options(writer, endian, ())?;
-                        0u32.write_options(writer, endian, ())?;
-                        resource.class_name.write_options(writer, endian, ())?;
-                        resource.name.write_options(writer, endian, ())?;
-                        writer.write_all(link_header)?;
-                        writer.write_all(body)?;
-                    }
-                    _ => todo!(),
-                }
-            }
+            let calculated_working_buffer_offset = write_block_objects(
+                writer,
+                endian,
+                i,
+                block
+                    .objects
+                    .iter()
+                    .map(|object| Ok((bigfile.object(&object.name)?, false)))
+                    .collect::<BffResult<Vec<_>>>()?,
+            )?;
 
             let block_end = writer.stream_position()?;
             let data_size = block_end - block_begin;
@@ -246,6 +323,67 @@ impl BigFileIo for BigFileV1_2002_45_19PC {
         Ok(())
     }
 
+    fn patch<S: Read + Write + Seek + SetLen>(
+        stream: &mut S,
+        _version: Version,
+        platform: Platform,
+        resource: &Resource,
+    ) -> BffResult<u64> {
+        let endian: Endian = platform.into();
+
+        let begin = stream.stream_position()?;
+        let mut header = Header::read_options(stream, endian, ())?;
+
+        let patch = patch_block(
+            stream,
+            endian,
+            header
+                .block_descriptions
+                .iter()
+                .map(|block| (block.object_count, block.padded_size)),
+            resource,
+            &HashSet::new(),
+        )?;
+        replace_block(stream, &patch)?;
+        let end = stream.stream_position()?;
+
+        let block = &mut header.block_descriptions[patch.index];
+        header.block_sector_padding_size = header.block_sector_padding_size
+            + (patch.data.len() as u64 - patch.data_size)
+            - (block.padded_size - block.data_size);
+        block.padded_size = patch.data.len() as u64;
+        block.data_size = patch.data_size;
+        block.working_buffer_offset = block
+            .working_buffer_offset
+            .max(patch.calculated_working_buffer_offset);
+
+        (
+            header.block_working_buffer_capacity_even,
+            header.block_working_buffer_capacity_odd,
+        ) = header
+            .block_descriptions
+            .iter()
+            .enumerate()
+            .fold((0, 0), |(even, odd), (i, block)| {
+                let capacity = block.padded_size + block.working_buffer_offset;
+                match i % 2 {
+                    0 => (even.max(capacity), odd),
+                    _ => (even, odd.max(capacity)),
+                }
+            });
+        header.total_padded_block_size = header
+            .block_descriptions
+            .iter()
+            .map(|block| block.padded_size)
+            .sum();
+        header.file_size = end;
+
+        stream.seek(SeekFrom::Start(begin))?;
+        header.write_options(stream, endian, ())?;
+
+        Ok(end)
+    }
+
     const NAME_TYPE: NameType = Asobo32;
 
     type ResourceType = Object;
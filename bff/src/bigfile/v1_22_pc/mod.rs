@@ -4,26 +4,29 @@ use std::io::{Read, Seek, SeekFrom, Write};
 
 use binrw::{binread, binrw, parser, BinRead, BinResult, BinWrite, Endian};
 
+use crate::bigfile::compression::record_object;
 use crate::bigfile::manifest::Manifest;
 use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::{cancelled_at, report, report_objects, Progress};
 use crate::bigfile::resource::ResourceData::{Data, SplitData};
 use crate::bigfile::versions::{Version, VersionTriple, VersionXple};
 use crate::bigfile::BigFile;
-use crate::helpers::{write_align_to, DynArray};
+use crate::error::BlockParseError;
+use crate::helpers::{checked_vec_parser, write_align_to, DynArray};
 use crate::names::NameType::{BlackSheep32, Kalisto32};
-use crate::names::{Name, NameType};
+use crate::names::{Name, NameContext, NameType};
 use crate::traits::BigFileIo;
 use crate::BffResult;
 
 #[binrw]
 #[derive(Debug)]
 pub struct Resource<const S: u32 = 12> {
-    #[br(temp)]
+    #[br(temp, assert(data_size >= S, "resource size {} is smaller than its {} byte header", data_size, S))]
     #[bw(calc = data.len() as u32 + S)]
     data_size: u32,
     class_name: Name,
     pub name: Name,
-    #[br(count = data_size - S)]
+    #[br(parse_with = checked_vec_parser, args((data_size - S) as usize))]
     data: Vec<u8>,
 }
 
@@ -80,7 +83,7 @@ pub struct Block {
     #[br(temp, try_calc = s.stream_position())]
     begin: u64,
     pub resources: DynArray<Resource>,
-    #[br(temp, try_calc = s.stream_position())]
+    #[br(temp, try_calc = s.stream_position(), assert(end - begin <= block_size as u64, "resources take {} bytes of a {} byte block", end - begin, block_size))]
     end: u64,
     #[br(temp, pad_after = block_size as u64 - (end - begin))]
     _padding: (),
@@ -98,7 +101,17 @@ impl BinWrite for Block {
         let begin = writer.stream_position()?;
         self.resources.write_options(writer, endian, ())?;
         let end = writer.stream_position()?;
-        vec![0u8; block_size as usize - (end - begin) as usize].write_be(writer)?;
+        let padding = (block_size as u64)
+            .checked_sub(end - begin)
+            .ok_or_else(|| binrw::Error::AssertFail {
+                pos: begin,
+                message: format!(
+                    "resources take {} bytes of a {} byte block",
+                    end - begin,
+                    block_size
+                ),
+            })?;
+        vec![0u8; padding as usize].write_be(writer)?;
         Ok(())
     }
 }
@@ -111,8 +124,22 @@ fn parse_blocks(block_size: u32) -> BinResult<Vec<Block>> {
     let end = reader.seek(SeekFrom::End(0))?;
     reader.seek(SeekFrom::Start(begin))?;
 
+    // Every block takes block_size bytes
+    let block_count = ((end - begin) / max(block_size, 1) as u64) as usize;
+
     while reader.stream_position()? != end {
-        blocks.push(Block::read_options(reader, endian, (block_size,))?);
+        report(Progress::Block {
+            index: blocks.len(),
+            count: block_count,
+        })
+        .map_err(cancelled_at(reader.stream_position()?))?;
+
+        let block_offset = reader.stream_position()?;
+        let block = Block::read_options(reader, endian, (block_size,))
+            .map_err(BlockParseError::at(block_offset, blocks.len()))?;
+        report_objects(block.resources.iter().map(|resource| resource.name))
+            .map_err(cancelled_at(reader.stream_position()?))?;
+        blocks.push(block);
     }
 
     Ok(blocks)
@@ -152,6 +179,9 @@ impl<const HAS_VERSION_TRIPLE: bool, const KALISTO: bool>
                 objects.push(crate::bigfile::manifest::ManifestObject {
                     name: resource.name,
                     compress: None,
+                    pooled: None,
+                    external: None,
+                    unknowns: None,
                 });
                 resources.insert(resource.name, resource.into());
             }
@@ -161,6 +191,9 @@ impl<const HAS_VERSION_TRIPLE: bool, const KALISTO: bool>
                 checksum: None,
                 compressed: None,
                 objects,
+                resource_counts: None,
+                data: None,
+                unknowns: None,
             });
         }
 
@@ -174,8 +207,11 @@ impl<const HAS_VERSION_TRIPLE: bool, const KALISTO: bool>
                 incredi_builder_string: None,
                 blocks,
                 pool: None,
+                header_unknowns: None,
+                compression: None,
             },
             objects: resources,
+            name_context: NameContext::current(),
         }
     }
 }
@@ -209,14 +245,28 @@ impl<const HAS_VERSION_TRIPLE: bool, const KALISTO: bool> BigFileIo
 
         let mut block_size = 0u32;
 
-        for block in bigfile.manifest.blocks.iter() {
+        for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
+            report(Progress::Block {
+                index: i,
+                count: bigfile.manifest.blocks.len(),
+            })?;
+
             let block_begin = writer.stream_position()?;
 
             (block.objects.len() as u32).write_options(writer, endian, ())?;
 
             for resource in block.objects.iter() {
-                let resource = bigfile.objects.get(&resource.name).unwrap();
+                let resource = bigfile.object(&resource.name)?;
                 Resource::<12>::dump_resource(resource, writer, endian)?;
+                record_object(
+                    Some(i),
+                    resource.class_name,
+                    resource.size(),
+                    resource.size(),
+                );
+                report(Progress::Object {
+                    name: resource.name,
+                })?;
             }
 
             write_align_to(writer, 0x20000, 0xCD)?;
@@ -2,17 +2,28 @@ use std::cmp::max;
 use std::collections::HashMap;
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
-use binrw::{args, binread, parser, BinRead, BinResult, BinWrite, Endian};
+use binrw::{binread, parser, BinRead, BinResult, BinWrite, Endian};
 
 use super::v1_22_pc::Resource as Resource12;
+use crate::bigfile::compression::{record_block, record_object};
 use crate::bigfile::manifest::Manifest;
 use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::{cancelled_at, report, report_objects, Progress};
 use crate::bigfile::versions::Version;
 use crate::bigfile::BigFile;
-use crate::helpers::{calculated_padded, read_align_to, write_align_to, DynArray};
+use crate::helpers::{
+    calculated_padded,
+    check_count,
+    check_decompressed_size,
+    checked_vec_parser,
+    par_map,
+    read_align_to,
+    write_align_to,
+    DynArray,
+};
 use crate::lz::{lzo_compress, lzo_decompress};
-use crate::names::NameType;
 use crate::names::NameType::Ubisoft64;
+use crate::names::{NameContext, NameType};
 use crate::traits::BigFileIo;
 use crate::BffResult;
 
@@ -42,43 +53,74 @@ impl BinWrite for Block {
 #[parser(reader, endian)]
 fn parse_blocks(decompressed_block_size: u32, block_sizes: &[u32]) -> BinResult<Vec<Block>> {
     let mut blocks = Vec::new();
+    let mut compressed_blocks = Vec::new();
 
-    for block_size in block_sizes {
+    // Read the compressed blocks first and decompress them all at once
+    for (index, block_size) in block_sizes.iter().enumerate() {
         let block_start = reader.stream_position()?;
+        report(Progress::Block {
+            index,
+            count: block_sizes.len(),
+        })
+        .map_err(cancelled_at(block_start))?;
 
         let checksum = Some(u32::read_options(reader, endian, ())?);
         let resource_count = u32::read_options(reader, endian, ())?;
 
         if *block_size != decompressed_block_size {
-            let mut compressed = vec![0; (*block_size - 8) as usize];
+            let mut compressed =
+                vec![0; check_count(reader, block_size.saturating_sub(8) as usize)?];
             reader.read_exact(&mut compressed)?;
-            let decompressed =
-                lzo_decompress(&compressed, decompressed_block_size as usize).unwrap();
-            let mut decompressed = Cursor::new(decompressed);
+            let decompressed_size =
+                check_decompressed_size(reader, decompressed_block_size as usize)?;
+            compressed_blocks.push((
+                index,
+                block_start,
+                resource_count,
+                compressed,
+                decompressed_size,
+            ));
             blocks.push(Block {
                 compressed: true,
                 checksum,
-                resources: Vec::<Resource>::read_options(
-                    &mut decompressed,
-                    endian,
-                    args! { count: resource_count as usize },
-                )?,
+                resources: Vec::new(),
             });
             read_align_to(reader, 2048)?;
         } else {
             blocks.push(Block {
                 compressed: false,
                 checksum,
-                resources: Vec::<Resource>::read_options(
-                    reader,
-                    endian,
-                    args! { count: resource_count as usize },
-                )?,
+                resources: checked_vec_parser(reader, endian, (resource_count as usize,))?,
             });
+            report_objects(blocks[index].resources.iter().map(|resource| resource.name))
+                .map_err(cancelled_at(block_start))?;
             reader.seek(SeekFrom::Start(block_start + *block_size as u64))?;
         }
     }
 
+    let decompressed_blocks = par_map(
+        compressed_blocks,
+        |(index, block_start, resource_count, compressed, decompressed_size)| {
+            lzo_decompress(&compressed, decompressed_size)
+                .map(|decompressed| (index, resource_count, decompressed))
+                .map_err(|err| binrw::Error::Custom {
+                    pos: block_start,
+                    err: Box::new(err),
+                })
+        },
+    );
+
+    for decompressed_block in decompressed_blocks {
+        let (index, resource_count, decompressed) = decompressed_block?;
+        blocks[index].resources = checked_vec_parser(
+            &mut Cursor::new(decompressed),
+            endian,
+            (resource_count as usize,),
+        )?;
+        report_objects(blocks[index].resources.iter().map(|resource| resource.name))
+            .map_err(cancelled_at(reader.stream_position()?))?;
+    }
+
     Ok(blocks)
 }
 
@@ -122,6 +164,9 @@ impl From<BigFileV2_0PC> for BigFile {
                 objects.push(crate::bigfile::manifest::ManifestObject {
                     name: resource.name,
                     compress: None,
+                    pooled: None,
+                    external: None,
+                    unknowns: None,
                 });
                 resources.insert(resource.name, resource.into());
             }
@@ -131,6 +176,9 @@ impl From<BigFileV2_0PC> for BigFile {
                 checksum: None,
                 compressed: Some(block.compressed),
                 objects,
+                resource_counts: None,
+                data: None,
+                unknowns: None,
             });
         }
 
@@ -144,8 +192,11 @@ impl From<BigFileV2_0PC> for BigFile {
                 incredi_builder_string: None,
                 blocks,
                 pool: None,
+                header_unknowns: None,
+                compression: None,
             },
             objects: resources,
+            name_context: NameContext::current(),
         }
     }
 }
@@ -176,12 +227,26 @@ impl BigFileIo for BigFileV2_0PC {
 
         let mut blocks = Vec::new();
 
-        for block in bigfile.manifest.blocks.iter() {
+        for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
+            report(Progress::Block {
+                index: i,
+                count: bigfile.manifest.blocks.len(),
+            })?;
+
             let mut block_writer = Cursor::new(Vec::new());
 
             for resource in block.objects.iter() {
-                let resource = bigfile.objects.get(&resource.name).unwrap();
+                let resource = bigfile.object(&resource.name)?;
                 Resource::dump_resource(resource, &mut block_writer, endian)?;
+                record_object(
+                    Some(i),
+                    resource.class_name,
+                    resource.size(),
+                    resource.size(),
+                );
+                report(Progress::Object {
+                    name: resource.name,
+                })?;
             }
 
             let block_data = block_writer.into_inner();
@@ -200,21 +265,32 @@ impl BigFileIo for BigFileV2_0PC {
         let mut block_sizes = Vec::new();
         let mut compression_type = CompressionType::None;
 
-        for (resource_count, checksum, compressed, mut block_data) in blocks {
+        let compressed_blocks = par_map(
+            blocks,
+            |(resource_count, checksum, compressed, mut block_data)| {
+                block_data.resize(decompressed_block_size as usize, 0);
+                if compressed {
+                    let mut compressed_data = Vec::new();
+                    lzo_compress(&block_data, &mut compressed_data)?;
+                    block_data = compressed_data;
+                }
+                BffResult::Ok((resource_count, checksum, compressed, block_data))
+            },
+        );
+
+        for (i, compressed_block) in compressed_blocks.into_iter().enumerate() {
+            let (resource_count, checksum, compressed, block_data) = compressed_block?;
             let block_begin = writer.stream_position()?;
 
             checksum.write_options(writer, endian, ())?;
 
             resource_count.write_options(writer, endian, ())?;
 
-            block_data.resize(decompressed_block_size as usize, 0);
-
             if compressed {
                 compression_type = CompressionType::Lzo;
-                lzo_compress(&block_data, writer)?;
-            } else {
-                writer.write_all(&block_data)?;
             }
+            writer.write_all(&block_data)?;
+            record_block(i, decompressed_block_size as usize, block_data.len());
 
             let block_end = writer.stream_position()?;
 
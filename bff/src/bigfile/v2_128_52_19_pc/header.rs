@@ -4,10 +4,10 @@ use binrw::*;
 use serde::Serialize;
 
 use crate::bigfile::versions::VersionOneple;
-use crate::helpers::DynArray;
+use crate::helpers::{checked_vec_parser, DynArray};
 use crate::names::Name;
 
-#[derive(Serialize, Debug, BinRead, BinWrite)]
+#[derive(Serialize, Debug, Default, BinRead, BinWrite)]
 pub struct DataDescription {
     pub resource_count: u32,
     pub padded_size: u64,
@@ -17,9 +17,11 @@ pub struct DataDescription {
 
 impl DataDescription {
     const SIZE: u64 = 28;
+    // The descriptions are padded to this many entries
+    const MAX_COUNT: u32 = 52;
 }
 
-#[derive(Serialize, Debug, BinRead)]
+#[derive(Serialize, Debug, Default, BinRead, BinWrite)]
 pub struct Resource {
     pub name: Name,
     pub class_name: Name,
@@ -36,8 +38,10 @@ pub struct Unknown {
     pub data: [u8; 16],
 }
 
-#[derive(Serialize, Debug, BinRead)]
+#[derive(Serialize, Debug, BinRead, BinWrite)]
 pub struct Resources {
+    #[br(assert(data_count <= DataDescription::MAX_COUNT, "{} data descriptions, at most {} fit in a resources map", data_count, DataDescription::MAX_COUNT))]
+    #[bw(assert(*data_count <= DataDescription::MAX_COUNT, "{} data descriptions, at most {} fit in a resources map", data_count, DataDescription::MAX_COUNT))]
     pub data_count: u32,
     pub data_offset: u32,
     pub working_buffer_offset: u32,
@@ -45,17 +49,18 @@ pub struct Resources {
     pub unk2: u64,
     pub padded_size: u64,
     pub padding_size: u64,
-    #[br(count = data_count, pad_after = DataDescription::SIZE * 52 - DataDescription::SIZE * data_count as u64)]
+    #[br(count = data_count, pad_after = DataDescription::SIZE * (DataDescription::MAX_COUNT - data_count) as u64)]
+    #[bw(pad_after = DataDescription::SIZE * (DataDescription::MAX_COUNT - *data_count) as u64)]
     pub data_descriptions: Vec<DataDescription>,
-    // Use a Vec here instead of DynArray because Resource doesn't impl BinWrite and binrw isn't smart with trait bounds
     pub resource_count: u32,
-    #[br(count = resource_count)]
+    #[br(parse_with = checked_vec_parser, args(resource_count as usize))]
     pub resources: Vec<Resource>,
     pub unk3: u64,
     pub unknown: DynArray<Unknown>,
     pub unk4: DynArray<u32>,
     pub resource_count2: u32,
-    #[br(count = resource_count2, align_after = 16)]
+    #[br(parse_with = checked_vec_parser, args(resource_count2 as usize))]
+    #[brw(align_after = 16)]
     pub resources2: Vec<Resource>,
 }
 
@@ -68,7 +73,11 @@ pub struct BlockDescription {
     pub data_resources_map_offset: u32,
 }
 
-#[derive(Serialize, Debug, BinRead, BinWrite)]
+impl BlockDescription {
+    pub const SIZE: u64 = 32;
+}
+
+#[derive(Serialize, Debug, BinRead, BinWrite, Copy, Clone)]
 #[brw(repr = u8)]
 pub enum BigFileType {
     Rtc = 0,
@@ -1,11 +1,36 @@
-use binrw::BinRead;
-use serde::Serialize;
+use std::cmp::min;
 
-use super::object::Object;
+use crate::bigfile::manifest::{ManifestBlock, ManifestObject};
 
-#[derive(BinRead, Serialize, Debug)]
-#[br(import(data_count: u32))]
-pub struct Data {
-    #[br(count = data_count, align_after = 2048)]
-    pub objects: Vec<Object>,
+pub struct BlockLayout<'a> {
+    pub resources: Vec<&'a [ManifestObject]>,
+    pub data: Vec<(Option<u64>, &'a [ManifestObject])>,
+}
+
+impl<'a> From<&'a ManifestBlock> for BlockLayout<'a> {
+    fn from(block: &'a ManifestBlock) -> Self {
+        let mut remaining = block.objects.as_slice();
+
+        let mut resources = Vec::new();
+        for count in block.resource_counts.iter().flatten() {
+            let (group, rest) = remaining.split_at(min(*count as usize, remaining.len()));
+            resources.push(group);
+            remaining = rest;
+        }
+
+        let mut data = Vec::new();
+        for data_description in block.data.iter().flatten() {
+            let (chunk, rest) =
+                remaining.split_at(min(data_description.object_count as usize, remaining.len()));
+            data.push((data_description.offset, chunk));
+            remaining = rest;
+        }
+
+        // Objects that the manifest doesn't place explicitly go in a trailing data chunk
+        if !remaining.is_empty() {
+            data.push((None, remaining));
+        }
+
+        Self { resources, data }
+    }
 }
@@ -5,10 +5,10 @@ use serde::Serialize;
 
 use crate::bigfile::v1_06_63_02_pc::header::BigFileType;
 use crate::bigfile::versions::VersionOneple;
-use crate::helpers::DynArray;
+use crate::helpers::{checked_vec_parser, DynArray};
 use crate::names::Name;
 
-#[derive(Serialize, Debug, BinRead, BinWrite)]
+#[derive(Serialize, Debug, Default, BinRead, BinWrite)]
 pub struct DataDescription {
     pub resource_count: u32,
     pub padded_size: u64,
@@ -18,9 +18,11 @@ pub struct DataDescription {
 
 impl DataDescription {
     const SIZE: u64 = 28;
+    // The descriptions are padded to this many entries
+    const MAX_COUNT: u32 = 52;
 }
 
-#[derive(Serialize, Debug, BinRead)]
+#[derive(Serialize, Debug, Default, BinRead, BinWrite)]
 pub struct Resource {
     pub name: Name,
     pub class_name: Name,
@@ -29,8 +31,10 @@ pub struct Resource {
     pub decompressed_size: u64,
 }
 
-#[derive(Serialize, Debug, BinRead)]
+#[derive(Serialize, Debug, BinRead, BinWrite)]
 pub struct Resources {
+    #[br(assert(data_count <= DataDescription::MAX_COUNT, "{} data descriptions, at most {} fit in a resources map", data_count, DataDescription::MAX_COUNT))]
+    #[bw(assert(*data_count <= DataDescription::MAX_COUNT, "{} data descriptions, at most {} fit in a resources map", data_count, DataDescription::MAX_COUNT))]
     pub data_count: u32,
     pub data_offset: u32,
     pub working_buffer_offset: u32,
@@ -38,13 +42,13 @@ pub struct Resources {
     pub unk2: u64,
     pub padded_size: u64,
     pub padding_size: u64,
-    #[br(count = data_count, pad_after = DataDescription::SIZE * 52 - DataDescription::SIZE * data_count as u64)]
+    #[br(count = data_count, pad_after = DataDescription::SIZE * (DataDescription::MAX_COUNT - data_count) as u64)]
+    #[bw(pad_after = DataDescription::SIZE * (DataDescription::MAX_COUNT - *data_count) as u64)]
     pub data_descriptions: Vec<DataDescription>,
-    // Use a Vec here instead of DynArray because Resource doesn't impl BinWrite and binrw isn't smart with trait bounds
     pub resource_count: u32,
-    #[br(count = resource_count)]
+    #[br(parse_with = checked_vec_parser, args(resource_count as usize))]
     pub resources: Vec<Resource>,
-    #[br(align_after = 2048)]
+    #[brw(align_after = 2048)]
     pub unk3: u64,
 }
 
@@ -57,6 +61,10 @@ pub struct BlockDescription {
     pub data_resources_map_offset: u32,
 }
 
+impl BlockDescription {
+    pub const SIZE: u64 = 32;
+}
+
 #[binrw]
 #[derive(Serialize, Debug)]
 pub struct Header {
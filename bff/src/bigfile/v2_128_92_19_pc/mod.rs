@@ -2,85 +2,146 @@ pub mod block;
 pub mod header;
 pub mod object;
 
+use std::cmp::max;
 use std::collections::HashMap;
 use std::io::{Read, Seek, SeekFrom, Write};
 
-use binrw::{BinRead, BinResult};
+use binrw::{BinRead, BinResult, BinWrite, Endian};
 use block::*;
 use header::*;
 use object::*;
 
+use crate::bigfile::compression::{record_object, CompressionParameters};
+use crate::bigfile::lazy::{BigFileIndex, IndexedObject};
 use crate::bigfile::manifest::*;
 use crate::bigfile::platforms::Platform;
+use crate::bigfile::progress::{cancelled_at, report, report_objects, Progress};
 use crate::bigfile::resource::Resource;
-use crate::bigfile::versions::Version;
+use crate::bigfile::versions::{Version, VersionXple};
 use crate::bigfile::BigFile;
+use crate::error::BlockParseError;
+use crate::helpers::{calculated_padded, check_count, par_map, read_align_to, write_align_to};
 use crate::names::NameType::Asobo64;
-use crate::names::{Name, NameType};
+use crate::names::{Name, NameContext, NameType};
 use crate::traits::BigFileIo;
 use crate::BffResult;
 
 pub struct BigFileV2_128_92_19PC;
 
-#[binrw::parser(reader, endian)]
-pub fn blocks_parser(
+// Same walk as v2_128_52 with the resources and data aligned to sectors
+fn walk_blocks<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
     block_descriptions: Vec<BlockDescription>,
-    objects: &mut HashMap<Name, Resource>,
+    mut read_object: impl FnMut(&mut R, usize) -> BinResult<(Name, bool)>,
 ) -> BinResult<Vec<ManifestBlock>> {
-    let mut blocks: Vec<ManifestBlock> = Vec::with_capacity(block_descriptions.len());
+    let block_count = block_descriptions.len();
+    let mut blocks: Vec<ManifestBlock> = Vec::with_capacity(block_count);
 
-    for block_description in block_descriptions {
-        reader.seek(SeekFrom::Start(
-            block_description.resources_map_offset as u64 * 2048,
-        ))?;
-        let resources = Resources::read_options(reader, endian, ())?;
+    for (block_index, block_description) in block_descriptions.into_iter().enumerate() {
+        report(Progress::Block {
+            index: block_index,
+            count: block_count,
+        })
+        .map_err(cancelled_at(reader.stream_position()?))?;
+
+        let resources_offset = block_description.resources_map_offset as u64 * 2048;
+        reader.seek(SeekFrom::Start(resources_offset))?;
+        let resources = Resources::read_options(reader, endian, ())
+            .map_err(BlockParseError::at(resources_offset, block_index))?;
+
+        // The data counts are only trusted once they are checked
+        let mut block_objects = Vec::with_capacity(resources.resources.len());
+        let resource_count = resources.resources.len() as u32;
+        let data = resources
+            .data_descriptions
+            .iter()
+            .map(|d| ManifestBlockData {
+                offset: Some(d.working_buffer_offset),
+                object_count: d.resource_count,
+            })
+            .collect::<Vec<_>>();
 
-        let mut block_objects = Vec::with_capacity(
-            resources.resources.len()
-                + resources
-                    .data_descriptions
-                    .iter()
-                    .map(|d| d.resource_count)
-                    .sum::<u32>() as usize,
-        );
         for object in resources.resources.into_iter() {
             reader.seek(SeekFrom::Start(object.offset as u64 * 2048))?;
-            let object = Object::read_options(reader, endian, ())?;
+            let (name, compress) = read_object(reader, block_index)?;
 
             block_objects.push(ManifestObject {
-                name: object.name,
-                compress: Some(object.compress),
+                name,
+                compress: Some(compress),
+                pooled: None,
+                external: None,
+                unknowns: None,
             });
-
-            objects.insert(object.name, object.into());
         }
 
         reader.seek(SeekFrom::Start(resources.data_offset as u64 * 2048))?;
 
         for data_description in resources.data_descriptions {
-            let data = Data::read_options(reader, endian, (data_description.resource_count,))?;
+            let data_offset = reader.stream_position()?;
+            let object_count = check_count(reader, data_description.resource_count as usize)
+                .map_err(BlockParseError::at(data_offset, block_index))?;
+
+            for _ in 0..object_count {
+                let (name, compress) = read_object(reader, block_index)?;
 
-            for object in data.objects.into_iter() {
                 block_objects.push(ManifestObject {
-                    name: object.name,
-                    compress: Some(object.compress),
+                    name,
+                    compress: Some(compress),
+                    pooled: None,
+                    external: None,
+                    unknowns: None,
                 });
-
-                objects.insert(object.name, object.into());
             }
+            read_align_to(reader, 2048)?;
         }
 
+        report_objects(block_objects.iter().map(|object| object.name))
+            .map_err(cancelled_at(reader.stream_position()?))?;
+
         blocks.push(ManifestBlock {
             offset: Some(resources.working_buffer_offset as u64),
             checksum: None,
             compressed: None,
             objects: block_objects,
+            resource_counts: Some(vec![resource_count]),
+            data: Some(data),
+            unknowns: None,
         });
     }
 
     Ok(blocks)
 }
 
+#[binrw::parser(reader, endian)]
+pub fn blocks_parser(
+    block_descriptions: Vec<BlockDescription>,
+    objects: &mut HashMap<Name, Resource>,
+) -> BinResult<Vec<ManifestBlock>> {
+    walk_blocks(reader, endian, block_descriptions, |reader, block_index| {
+        let object = object_parser(reader, endian, (block_index,))?;
+        let (name, compress) = (object.name, object.compress);
+        objects.insert(name, object.into());
+        Ok((name, compress))
+    })
+}
+
+#[binrw::parser(reader, endian)]
+pub fn blocks_index_parser(
+    block_descriptions: Vec<BlockDescription>,
+    objects: &mut HashMap<Name, IndexedObject>,
+) -> BinResult<Vec<ManifestBlock>> {
+    walk_blocks(reader, endian, block_descriptions, |reader, _| {
+        let offset = reader.stream_position()?;
+        let object = ObjectHeader::read_options(reader, endian, ())?;
+        objects.insert(
+            object.name,
+            IndexedObject::stored(object.class_name, object.size, offset),
+        );
+        Ok((object.name, object.compress))
+    })
+}
+
 impl BigFileIo for BigFileV2_128_92_19PC {
     fn read<R: Read + Seek>(
         reader: &mut R,
@@ -108,17 +169,284 @@ impl BigFileIo for BigFileV2_128_92_19PC {
                 incredi_builder_string: None,
                 blocks,
                 pool: None,
+                header_unknowns: None,
+                compression: None,
             },
             objects,
+            name_context: NameContext::current(),
+        })
+    }
+
+    fn read_index<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFileIndex> {
+        let endian = platform.into();
+        let header = Header::read_options(reader, endian, ())?;
+
+        let mut objects = HashMap::new();
+
+        let blocks = blocks_index_parser(
+            reader,
+            endian,
+            (header.block_descriptions.inner, &mut objects),
+        )?;
+
+        Ok(BigFileIndex {
+            manifest: Manifest {
+                version,
+                version_xple: Some(header.version_oneple.into()),
+                platform,
+                bigfile_type: Some(header.bigfile_type.into()),
+                pool_manifest_unused: None,
+                incredi_builder_string: None,
+                blocks,
+                pool: None,
+                header_unknowns: None,
+                compression: None,
+            },
+            objects,
+            name_context: NameContext::current(),
         })
     }
 
     fn write<W: Write + Seek>(
-        _bigfile: &BigFile,
-        _writer: &mut W,
+        bigfile: &BigFile,
+        writer: &mut W,
         _tag: Option<&str>,
     ) -> BffResult<()> {
-        todo!()
+        let endian: Endian = bigfile.manifest.platform.into();
+        let lz4_mode = CompressionParameters::lz4(&bigfile.manifest.compression);
+
+        let begin = writer.stream_position()?;
+
+        // The block descriptions immediately follow the 4096 aligned header
+        let block_descriptions_begin = 4096u64;
+        writer.seek(SeekFrom::Start(
+            block_descriptions_begin
+                + 4
+                + BlockDescription::SIZE * bigfile.manifest.blocks.len() as u64,
+        ))?;
+        write_align_to(writer, 2048, 0x00)?;
+
+        let mut block_sector_padding_size = 0u64;
+        let mut total_decompressed_size = 0u64;
+        let mut total_resource_count = 0u32;
+
+        let mut block_descriptions = Vec::with_capacity(bigfile.manifest.blocks.len());
+        let mut block_padded_sizes = Vec::with_capacity(bigfile.manifest.blocks.len());
+
+        for (i, block) in bigfile.manifest.blocks.iter().enumerate() {
+            report(Progress::Block {
+                index: i,
+                count: bigfile.manifest.blocks.len(),
+            })?;
+
+            let layout = BlockLayout::from(block);
+            let block_resources = layout
+                .resources
+                .iter()
+                .flat_map(|x| x.iter())
+                .collect::<Vec<_>>();
+
+            // Write a placeholder resources map to reserve space for it, it is rewritten once the
+            // offsets and sizes are known
+            let resources_map_begin = writer.stream_position()?;
+            let mut resources = Resources {
+                data_count: layout.data.len() as u32,
+                data_offset: 0,
+                working_buffer_offset: 0,
+                unk1: 0,
+                unk2: 0,
+                padded_size: 0,
+                padding_size: 0,
+                data_descriptions: layout
+                    .data
+                    .iter()
+                    .map(|_| DataDescription::default())
+                    .collect(),
+                resource_count: block_resources.len() as u32,
+                resources: block_resources
+                    .iter()
+                    .map(|_| header::Resource::default())
+                    .collect(),
+                unk3: 0,
+            };
+            resources.write_options(writer, endian, ())?;
+
+            // Compress the bodies in parallel, the objects are then written in order
+            let block_objects = block_resources
+                .iter()
+                .copied()
+                .chain(layout.data.iter().flat_map(|(_, objects)| objects.iter()))
+                .map(|object| bigfile.object(&object.name))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut compressed_bodies = par_map(block_objects, |resource| {
+                Object::compress_body(resource, endian, lz4_mode)
+            })
+            .into_iter();
+
+            let mut padding_size = 0u64;
+
+            for ((entry, object), compressed_body) in resources
+                .resources
+                .iter_mut()
+                .zip(block_resources)
+                .zip(compressed_bodies.by_ref())
+            {
+                let resource = bigfile.object(&object.name)?;
+                let begin_resource = writer.stream_position()?;
+                Object::write_resource(resource, compressed_body?, writer, endian)?;
+                let end_resource = writer.stream_position()?;
+                record_object(
+                    Some(i),
+                    resource.class_name,
+                    resource.size(),
+                    (end_resource - begin_resource) as usize - Object::HEADER_SIZE as usize,
+                );
+                report(Progress::Object {
+                    name: resource.name,
+                })?;
+                padding_size += write_align_to(writer, 2048, 0x00)? as u64;
+
+                *entry = header::Resource {
+                    name: resource.name,
+                    class_name: resource.class_name,
+                    offset: (begin_resource / 2048) as u32,
+                    size: end_resource - begin_resource,
+                    decompressed_size: (Object::HEADER_SIZE as usize + resource.size()) as u64,
+                };
+            }
+
+            let data_begin = writer.stream_position()?;
+
+            for (data_description, (offset, objects)) in resources
+                .data_descriptions
+                .iter_mut()
+                .zip(layout.data.iter())
+            {
+                let data_description_begin = writer.stream_position()?;
+
+                let mut calculated_working_buffer_offset = 0usize;
+
+                for (object, compressed_body) in objects.iter().zip(compressed_bodies.by_ref()) {
+                    let resource = bigfile.object(&object.name)?;
+                    let begin_resource = writer.stream_position()?;
+                    Object::write_resource(resource, compressed_body?, writer, endian)?;
+                    let end_resource = writer.stream_position()?;
+                    record_object(
+                        Some(i),
+                        resource.class_name,
+                        resource.size(),
+                        (end_resource - begin_resource) as usize - Object::HEADER_SIZE as usize,
+                    );
+                    report(Progress::Object {
+                        name: resource.name,
+                    })?;
+
+                    if resource.compress {
+                        let needed_working_buffer_offset = if resource.size()
+                            > (begin_resource - data_description_begin) as usize
+                        {
+                            resource.size()
+                        } else {
+                            0
+                        };
+
+                        calculated_working_buffer_offset = max(
+                            needed_working_buffer_offset,
+                            calculated_working_buffer_offset,
+                        );
+                    }
+                }
+
+                let data_description_end = writer.stream_position()?;
+                let size = data_description_end - data_description_begin;
+                let padding = write_align_to(writer, 2048, 0x00)? as u64;
+                padding_size += padding;
+
+                *data_description = DataDescription {
+                    resource_count: objects.len() as u32,
+                    padded_size: size + padding,
+                    size,
+                    working_buffer_offset:
+                        offset.unwrap_or(
+                            calculated_padded(calculated_working_buffer_offset, 2048) as u64
+                        ),
+                };
+            }
+
+            let block_end = writer.stream_position()?;
+
+            resources.data_offset = (data_begin / 2048) as u32;
+            resources.working_buffer_offset = block.offset.unwrap_or(
+                resources
+                    .data_descriptions
+                    .iter()
+                    .map(|x| x.working_buffer_offset)
+                    .max()
+                    .unwrap_or(0),
+            ) as u32;
+            resources.padded_size = block_end - resources_map_begin;
+            resources.padding_size = padding_size;
+
+            writer.seek(SeekFrom::Start(resources_map_begin))?;
+            resources.write_options(writer, endian, ())?;
+            writer.seek(SeekFrom::Start(block_end))?;
+
+            block_sector_padding_size += padding_size;
+            total_decompressed_size += block
+                .objects
+                .iter()
+                .map(|x| {
+                    bigfile
+                        .object(&x.name)
+                        .map(|resource| resource.size() as u64)
+                })
+                .sum::<Result<u64, _>>()?;
+            total_resource_count += block.objects.len() as u32;
+            block_padded_sizes.push(resources.padded_size);
+
+            block_descriptions.push(BlockDescription {
+                unk1: 0,
+                unk2: 0,
+                unk3: 0,
+                resources_map_offset: (resources_map_begin / 2048) as u32,
+                data_resources_map_offset: (data_begin / 2048) as u32,
+            });
+        }
+
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(begin))?;
+
+        let header = Header {
+            version_oneple: match bigfile.manifest.version_xple.unwrap_or(0.into()) {
+                VersionXple::Oneple(x) | VersionXple::Triple((x, _, _)) => x,
+            },
+            bigfile_type: bigfile
+                .manifest
+                .bigfile_type
+                .unwrap_or(BigFileType::Normal)
+                .into(),
+            block_description_offset: (block_descriptions_begin / 2048) as u32,
+            unk1: 0,
+            pool_offset: 0,
+            unk3: 0,
+            unk4: 0,
+            unk5: 0,
+            total_padded_block_size: block_padded_sizes.iter().sum(),
+            block_sector_padding_size,
+            file_size: end,
+            total_decompressed_size,
+            zero: 0,
+            total_resource_count,
+            block_descriptions: block_descriptions.into(),
+        };
+        header.write_options(writer, endian, ())?;
+
+        writer.seek(SeekFrom::Start(end))?;
+        Ok(())
     }
 
     const NAME_TYPE: NameType = Asobo64;
@@ -1,23 +1,19 @@
-use std::io::{Read, Seek, Write};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
-use binrw::{args, binread, parser, BinRead, BinResult, BinWrite, Endian};
+use binrw::{binread, parser, BinRead, BinResult, BinWrite, Endian};
 use serde::Serialize;
 
 use crate::bigfile::resource::Resource;
 use crate::bigfile::resource::ResourceData::SplitData;
-use crate::lz::lz4_decompress_body_parser;
+use crate::error::{BlockParseError, ObjectParseError};
+use crate::helpers::checked_vec_parser;
+use crate::lz::{lz4_compress_body_writer, lz4_decompress_body_parser, Lz4Mode};
 use crate::names::{Name, NameAsobo64};
 
 #[parser(reader, endian)]
 pub fn body_parser(decompressed_size: u32, compressed_size: u32) -> BinResult<Vec<u8>> {
     if compressed_size == 0 {
-        Vec::<u8>::read_options(
-            reader,
-            endian,
-            args! {
-                count: decompressed_size as usize,
-            },
-        )
+        checked_vec_parser(reader, endian, (decompressed_size as usize,))
     } else {
         lz4_decompress_body_parser(reader, endian, (decompressed_size, compressed_size))
     }
@@ -48,7 +44,7 @@ pub struct Object {
     _zero: u32,
     #[br(calc = compressed_body_size != 0)]
     pub compress: bool,
-    #[br(count = link_header_size)]
+    #[br(parse_with = checked_vec_parser, args(link_header_size as usize))]
     #[serde(skip_serializing)]
     pub link_header: Vec<u8>,
     #[br(parse_with = body_parser, args(decompressed_body_size, compressed_body_size))]
@@ -56,7 +52,102 @@ pub struct Object {
     pub body: Vec<u8>,
 }
 
+#[binread]
+#[derive(Debug)]
+pub struct ObjectHeader {
+    pub class_name: Name,
+    pub name: Name,
+    #[br(temp)]
+    _link_name: Name,
+    #[br(temp)]
+    _size: u32,
+    #[br(temp)]
+    link_header_size: u32,
+    #[br(temp)]
+    decompressed_body_size: u32,
+    #[br(temp)]
+    _compression_type: CompressionType,
+    #[br(temp)]
+    compressed_body_size: u32,
+    #[br(temp)]
+    _zero: u32,
+    #[br(calc = compressed_body_size != 0)]
+    pub compress: bool,
+    #[br(calc = (link_header_size + decompressed_body_size) as usize)]
+    pub size: usize,
+    #[br(temp, pad_after = link_header_size + if compress { compressed_body_size } else { decompressed_body_size })]
+    _body: (),
+}
+
+// Read the object at the reader's position, failures name the object they happened in
+#[parser(reader, endian)]
+pub fn object_parser(block_index: usize) -> BinResult<Object> {
+    let offset = reader.stream_position()?;
+    let header = ObjectHeader::read_options(reader, endian, ())
+        .map_err(BlockParseError::at(offset, block_index))?;
+    reader.seek(SeekFrom::Start(offset))?;
+    Object::read_options(reader, endian, ()).map_err(ObjectParseError::at(
+        offset,
+        block_index,
+        header.name,
+        header.class_name,
+    ))
+}
+
 impl Object {
+    pub const HEADER_SIZE: u32 = 48;
+
+    // Compress the body of the resource apart from writing it so the objects of a block can be
+    // compressed in parallel
+    pub fn compress_body(
+        resource: &Resource,
+        endian: Endian,
+        mode: Lz4Mode,
+    ) -> BinResult<Option<Vec<u8>>> {
+        match (&resource.data, resource.compress) {
+            (SplitData { body, .. }, true) => {
+                let mut compressed_body = Cursor::new(Vec::new());
+                lz4_compress_body_writer(body, &mut compressed_body, endian, (mode,))?;
+                Ok(Some(compressed_body.into_inner()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn write_resource<W: Write + Seek>(
+        resource: &Resource,
+        compressed_body: Option<Vec<u8>>,
+        writer: &mut W,
+        endian: Endian,
+    ) -> BinResult<()> {
+        match (&resource.data, compressed_body) {
+            (SplitData { link_header, body }, Some(compressed_body)) => {
+                let compressed_body_size = compressed_body.len() as u32;
+                resource.class_name.write_options(writer, endian, ())?;
+                resource.name.write_options(writer, endian, ())?;
+                resource
+                    .link_name
+                    .unwrap_or(NameAsobo64::default().into())
+                    .write_options(writer, endian, ())?;
+                (link_header.len() as u32 + compressed_body_size).write_options(
+                    writer,
+                    endian,
+                    (),
+                )?;
+                (link_header.len() as u32).write_options(writer, endian, ())?;
+                (body.len() as u32).write_options(writer, endian, ())?;
+                CompressionType::LZ4.write_options(writer, endian, ())?;
+                compressed_body_size.write_options(writer, endian, ())?;
+                0u32.write_options(writer, endian, ())?;
+                writer.write_all(link_header)?;
+                writer.write_all(&compressed_body)?;
+            }
+            (SplitData { .. }, None) => Self::dump_resource(resource, writer, endian)?,
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
     pub fn dump_resource<W: Write + Seek>(
         resource: &Resource,
         writer: &mut W,
@@ -0,0 +1,27 @@
+use std::io::{Read, Seek};
+
+use serde::Serialize;
+
+use crate::bigfile::platforms::Platform;
+use crate::bigfile::{probe, BigFile};
+use crate::names::NameContext;
+use crate::BffResult;
+
+#[derive(Serialize, Debug)]
+pub struct ChecksumMismatch {
+    pub block_index: usize,
+    pub expected: i32,
+    pub actual: i32,
+}
+
+impl BigFile {
+    // Verify a BigFile whose platform is not known, the hint is used if its endianness matches
+    pub fn verify_auto<R: Read + Seek>(
+        reader: &mut R,
+        hint: Option<Platform>,
+        name_context: &NameContext,
+    ) -> BffResult<Vec<ChecksumMismatch>> {
+        let (_, platform) = probe::probe(reader, hint)?;
+        Self::verify_platform(reader, platform, name_context)
+    }
+}
@@ -2,9 +2,10 @@ use std::ffi::OsString;
 
 use derive_more::{Constructor, Display, Error, From};
 
+use crate::bigfile::manifest::BigFileType;
 use crate::bigfile::platforms::{Platform, Style};
 use crate::bigfile::versions::Version;
-use crate::names::Name;
+use crate::names::{Name, NameType};
 
 #[derive(Debug, Constructor, Display, Error)]
 #[display(
@@ -32,6 +33,17 @@ pub struct UnimplementedVersionPlatformError {
     pub platform: Platform,
 }
 
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Salvaging is not supported for BigFile version, platform combination: {}, {}",
+    version,
+    platform
+)]
+pub struct SalvageUnsupportedError {
+    pub version: Version,
+    pub platform: Platform,
+}
+
 #[derive(Debug, Constructor, Display, Error)]
 #[display(fmt = "Unsupported BigFile version: {}", version)]
 pub struct UnimplementedVersionError {
@@ -44,6 +56,12 @@ pub struct InvalidExtensionError {
     pub extension: OsString,
 }
 
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "Invalid platform {}", platform)]
+pub struct InvalidPlatformError {
+    pub platform: String,
+}
+
 #[derive(Debug, Constructor, Display, Error)]
 #[display(fmt = "Invalid Platform/Style combination: {} {}", platform, style)]
 pub struct InvalidPlatformStyleError {
@@ -51,16 +69,338 @@ pub struct InvalidPlatformStyleError {
     pub style: Style,
 }
 
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "Expected a Common BigFile, found {:?}", bigfile_type)]
+pub struct NotCommonBigFileError {
+    pub bigfile_type: Option<BigFileType>,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "Objects not found in the Common BigFile: {:?}", names)]
+pub struct UnresolvedExternalObjectsError {
+    pub names: Vec<Name>,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Object {} is stored locally but this BigFile references its objects in the Common BigFile",
+    object_name
+)]
+pub struct LocalObjectInReferencingBigFileError {
+    pub object_name: Name,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Names with no known string cannot be converted to {:?}: {:?}",
+    name_type,
+    names
+)]
+pub struct UnknownNamesError {
+    pub name_type: NameType,
+    pub names: Vec<Name>,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Name {:?} does not hash to {:?} with {:?}",
+    string,
+    name,
+    name_type
+)]
+pub struct NameHashMismatchError {
+    pub name_type: NameType,
+    pub name: Name,
+    pub string: String,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Object {} of class {} cannot be converted to {}: {}",
+    object_name,
+    class_name,
+    platform,
+    source
+)]
+pub struct RetargetObjectError {
+    pub object_name: Name,
+    pub class_name: Name,
+    pub platform: Platform,
+    pub source: Box<Error>,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Cannot merge BigFiles with different {}: {} and {}",
+    property,
+    first,
+    second
+)]
+pub struct MergeMismatchError {
+    pub property: &'static str,
+    pub first: String,
+    pub second: String,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "Conflicting objects in merged BigFiles: {:?}", object_names)]
+pub struct MergeConflictError {
+    pub object_names: Vec<Name>,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Object {} is not split into a link header and a body",
+    object_name
+)]
+pub struct UnsplitObjectError {
+    pub object_name: Name,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "Object {} not found", object_name)]
+pub struct ObjectNotFoundError {
+    pub object_name: Name,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Object {} is pooled, patching pooled objects is not supported",
+    object_name
+)]
+pub struct PooledObjectPatchError {
+    pub object_name: Name,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "The pooled flag of object {} disagrees with the pool manifest, remove the pool manifest to generate it from the flags",
+    object_name
+)]
+pub struct PoolFlagMismatchError {
+    pub object_name: Name,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "The pool manifest refers to {} {} but has {} of them",
+    kind,
+    index,
+    count
+)]
+pub struct PoolIndexError {
+    pub kind: &'static str,
+    pub index: usize,
+    pub count: usize,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Object {} has a pool object entry that no object entry index refers to",
+    object_name
+)]
+pub struct UnreferencedPoolObjectError {
+    pub object_name: Name,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Failed to parse block {} at offset {:#x}: {}",
+    block_index,
+    offset,
+    source
+)]
+pub struct BlockParseError {
+    pub offset: u64,
+    pub block_index: usize,
+    pub source: Box<Error>,
+}
+
+impl BlockParseError {
+    // Name the block in the errors of binrw parsers
+    pub(crate) fn at(offset: u64, block_index: usize) -> impl FnOnce(binrw::Error) -> binrw::Error {
+        move |err| binrw::Error::Custom {
+            pos: offset,
+            err: Box::new(Self::new(offset, block_index, Box::new(err.into()))),
+        }
+    }
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Failed to parse object {} of class {} in block {} at offset {:#x}: {}",
+    object_name,
+    class_name,
+    block_index,
+    offset,
+    source
+)]
+pub struct ObjectParseError {
+    pub offset: u64,
+    pub block_index: usize,
+    pub object_name: Name,
+    pub class_name: Name,
+    pub source: Box<Error>,
+}
+
+impl ObjectParseError {
+    // Name the object in the errors of binrw parsers
+    pub(crate) fn at(
+        offset: u64,
+        block_index: usize,
+        object_name: Name,
+        class_name: Name,
+    ) -> impl FnOnce(binrw::Error) -> binrw::Error {
+        move |err| binrw::Error::Custom {
+            pos: offset,
+            err: Box::new(Self::new(
+                offset,
+                block_index,
+                object_name,
+                class_name,
+                Box::new(err.into()),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Unexpected data at offset {:#x}, the BigFile is {} bytes long",
+    offset,
+    len
+)]
+pub struct TrailingDataError {
+    pub offset: u64,
+    pub len: u64,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Expected the pool at offset {:#x}, found it at {:#x}",
+    expected,
+    offset
+)]
+pub struct PoolOffsetMismatchError {
+    pub expected: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "The {} size in the compressed data at offset {:#x} is {} but the object header says {}",
+    field,
+    offset,
+    found,
+    expected
+)]
+pub struct CompressedSizeMismatchError {
+    pub offset: u64,
+    pub field: &'static str,
+    pub expected: u32,
+    pub found: u32,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "Expected {} {} but found {}", expected, array, found)]
+pub struct ArrayLengthMismatchError {
+    pub array: &'static str,
+    pub expected: usize,
+    pub found: usize,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "LZ4 failed with error code {}", code)]
+pub struct Lz4Error {
+    pub code: i32,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(fmt = "LZO failed with error code {}", code)]
+pub struct LzoError {
+    pub code: i32,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Invalid FAT line {:?}, expected a path, an offset and a size",
+    line
+)]
+pub struct InvalidFatLineError {
+    pub line: String,
+}
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Invalid name line {:?}, expected a hash and a quoted string",
+    line
+)]
+pub struct InvalidNameLineError {
+    pub line: String,
+}
+
+#[derive(Debug, Display, Error)]
+#[display(fmt = "Names are not valid Windows-1252")]
+pub struct InvalidNamesEncodingError;
+
+#[derive(Debug, Constructor, Display, Error)]
+#[display(
+    fmt = "Read limit on the {} exceeded at offset {:#x}, {} is larger than {}",
+    kind,
+    offset,
+    value,
+    limit
+)]
+pub struct ReadLimitError {
+    pub offset: u64,
+    pub kind: &'static str,
+    pub value: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Display, Error)]
+#[display(fmt = "Cancelled by the progress observer")]
+pub struct CancelledError;
+
 #[derive(Debug, Display, Error, From)]
 pub enum Error {
+    ArrayLengthMismatch(ArrayLengthMismatchError),
     BinRW(binrw::Error),
+    BlockParse(BlockParseError),
+    Cancelled(CancelledError),
+    CompressedSizeMismatch(CompressedSizeMismatchError),
     Fmt(std::fmt::Error),
     InvalidExtension(InvalidExtensionError),
+    InvalidFatLine(InvalidFatLineError),
+    InvalidNameLine(InvalidNameLineError),
+    InvalidNamesEncoding(InvalidNamesEncodingError),
+    InvalidPlatform(InvalidPlatformError),
     InvalidPlatformStyle(InvalidPlatformStyleError),
     Io(std::io::Error),
+    LocalObjectInReferencingBigFile(LocalObjectInReferencingBigFileError),
+    Lz4(Lz4Error),
+    Lzo(LzoError),
+    MergeConflict(MergeConflictError),
+    MergeMismatch(MergeMismatchError),
+    NameHashMismatch(NameHashMismatchError),
+    NotCommonBigFile(NotCommonBigFileError),
+    ObjectNotFound(ObjectNotFoundError),
+    ObjectParse(ObjectParseError),
     ParseInt(std::num::ParseIntError),
+    PoolFlagMismatch(PoolFlagMismatchError),
+    PoolIndex(PoolIndexError),
+    PoolOffsetMismatch(PoolOffsetMismatchError),
+    PooledObjectPatch(PooledObjectPatchError),
+    ReadLimit(ReadLimitError),
+    RetargetObject(RetargetObjectError),
+    SalvageUnsupported(SalvageUnsupportedError),
+    TrailingData(TrailingDataError),
     UnimplementedClass(UnimplementedClassError),
     UnimplementedVersion(UnimplementedVersionError),
     UnimplementedVersionPlatform(UnimplementedVersionPlatformError),
+    UnknownNames(UnknownNamesError),
+    UnreferencedPoolObject(UnreferencedPoolObjectError),
+    UnresolvedExternalObjects(UnresolvedExternalObjectsError),
+    UnsplitObject(UnsplitObjectError),
     Utf8(std::string::FromUtf8Error),
 }
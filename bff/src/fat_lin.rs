@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::io::{BufRead, Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 
+use crate::error::InvalidFatLineError;
 use crate::helpers::write_align_to;
 use crate::BffResult;
 
@@ -23,11 +24,13 @@ impl Fat {
         for line in reader.lines() {
             let line = line?;
             let components = line.rsplitn(3, ' ').collect::<Vec<_>>();
-            assert_eq!(components.len(), 3); // TODO: Use a real error
+            let [size, offset, path] = components[..] else {
+                return Err(InvalidFatLineError::new(line).into());
+            };
             fat.entries.push(FatEntry {
-                path: PathBuf::from(components[2]),
-                offset: components[1].parse()?,
-                size: components[0].parse()?,
+                path: PathBuf::from(path),
+                offset: offset.parse()?,
+                size: size.parse()?,
             });
         }
 
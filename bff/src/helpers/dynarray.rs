@@ -2,10 +2,31 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 
 use bff_derive::ReferencedNames;
-use binrw::{binrw, BinRead, BinWrite};
+use binrw::io::{Read, Seek};
+use binrw::{binrw, BinRead, BinResult, BinWrite, Endian};
 use derive_more::{Deref, DerefMut};
 use serde::{Deserialize, Serialize};
 
+use crate::helpers::check_count;
+
+fn count_parser<R: Read + Seek, SizeType>(
+    reader: &mut R,
+    endian: Endian,
+    _args: (),
+) -> BinResult<SizeType>
+where
+    SizeType: BinRead + TryInto<usize>,
+    for<'a> <SizeType as BinRead>::Args<'a>: Default,
+    <SizeType as TryInto<usize>>::Error: Debug,
+    usize: TryInto<SizeType>,
+    <usize as TryInto<SizeType>>::Error: Debug,
+{
+    let count = SizeType::read_options(reader, endian, <_>::default())?;
+    Ok(check_count(reader, count.try_into().unwrap())?
+        .try_into()
+        .unwrap())
+}
+
 #[binrw]
 #[derive(Debug, Serialize, Deref, DerefMut, Deserialize, ReferencedNames)]
 #[serde(transparent)]
@@ -23,7 +44,7 @@ where
     usize: TryInto<SizeType>,
     <usize as TryInto<SizeType>>::Error: Debug,
 {
-    #[br(temp)]
+    #[br(temp, parse_with = count_parser)]
     #[bw(calc = inner.len().try_into().unwrap())]
     count: SizeType,
     #[deref]
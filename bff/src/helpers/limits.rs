@@ -0,0 +1,135 @@
+use std::cell::Cell;
+use std::io::{Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+
+use binrw::{args, BinRead, BinResult, Endian};
+
+use crate::error::ReadLimitError;
+
+// Lengths read from a BigFile are checked against these before anything is allocated, so a
+// corrupted length fails the read instead of aborting on allocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    // Largest element count of an array, string or map
+    pub max_count: usize,
+    // Largest buffer a decompressor may produce
+    pub max_decompressed_size: usize,
+    // Reject counts larger than the number of bytes left in the stream, every element takes at
+    // least one byte
+    pub check_remaining: bool,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        Self {
+            max_count: 0x1000000,
+            max_decompressed_size: 0x40000000,
+            check_remaining: true,
+        }
+    }
+}
+
+thread_local! {
+    static READ_LIMITS: Cell<ReadLimits> = Cell::new(ReadLimits::default());
+}
+
+impl ReadLimits {
+    pub fn unlimited() -> Self {
+        Self {
+            max_count: usize::MAX,
+            max_decompressed_size: usize::MAX,
+            check_remaining: false,
+        }
+    }
+
+    pub fn current() -> Self {
+        READ_LIMITS.with(Cell::get)
+    }
+
+    // Make these the current thread's limits until the guard is dropped
+    pub fn enter(&self) -> ReadLimitsGuard {
+        let previous = READ_LIMITS.with(|limits| limits.replace(*self));
+        ReadLimitsGuard {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+pub struct ReadLimitsGuard {
+    previous: ReadLimits,
+    // The guard restores the limits of the thread it was created on
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ReadLimitsGuard {
+    fn drop(&mut self) {
+        READ_LIMITS.with(|limits| limits.set(self.previous));
+    }
+}
+
+// Check an element count read just before the reader's position
+pub fn check_count<R: Seek>(reader: &mut R, count: usize) -> BinResult<usize> {
+    let limits = ReadLimits::current();
+    let offset = reader.stream_position()?;
+
+    if count > limits.max_count {
+        return Err(binrw::Error::Custom {
+            pos: offset,
+            err: Box::new(ReadLimitError::new(
+                offset,
+                "count",
+                count,
+                limits.max_count,
+            )),
+        });
+    }
+
+    if limits.check_remaining {
+        let len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let remaining = len.saturating_sub(offset) as usize;
+        if count > remaining {
+            return Err(binrw::Error::Custom {
+                pos: offset,
+                err: Box::new(ReadLimitError::new(
+                    offset,
+                    "remaining bytes",
+                    count,
+                    remaining,
+                )),
+            });
+        }
+    }
+
+    Ok(count)
+}
+
+// Read an array whose element count was read from the BigFile, for use with parse_with in place
+// of count
+pub fn checked_vec_parser<R: Read + Seek, T: for<'a> BinRead<Args<'a> = ()>>(
+    reader: &mut R,
+    endian: Endian,
+    (count,): (usize,),
+) -> BinResult<Vec<T>> {
+    let count = check_count(reader, count)?;
+    Vec::<T>::read_options(reader, endian, args! { count, inner: () })
+}
+
+// Check the size a decompressor is about to produce from the data at the reader's position
+pub fn check_decompressed_size<R: Seek>(reader: &mut R, size: usize) -> BinResult<usize> {
+    let limit = ReadLimits::current().max_decompressed_size;
+    if size > limit {
+        let offset = reader.stream_position()?;
+        return Err(binrw::Error::Custom {
+            pos: offset,
+            err: Box::new(ReadLimitError::new(
+                offset,
+                "decompressed size",
+                size,
+                limit,
+            )),
+        });
+    }
+    Ok(size)
+}
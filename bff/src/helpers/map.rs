@@ -8,6 +8,8 @@ use derive_more::Deref;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::helpers::check_count;
+
 #[derive(Debug, Serialize, Deref, Deserialize, ReferencedNames)]
 #[serde(transparent)]
 pub struct BffMap<KeyType: Eq + Hash, ValueType, SizeType = u32> {
@@ -34,10 +36,11 @@ where
         _args: Self::Args<'_>,
     ) -> BinResult<Self> {
         let size = SizeType::read_options(reader, endian, <_>::default())?;
+        let size = check_count(reader, size.try_into().unwrap())?;
 
         let mut map = IndexMap::new();
 
-        for _ in 0..size.try_into().unwrap() {
+        for _ in 0..size {
             let key = KeyType::read_options(reader, endian, <_>::default())?;
             let value = ValueType::read_options(reader, endian, <_>::default())?;
             map.insert(key, value);
@@ -2,12 +2,17 @@ use std::io::{Read, Seek, SeekFrom, Write};
 
 use binrw::BinResult;
 
+use crate::error::TrailingDataError;
+use crate::BffResult;
+
 mod dynarray;
 mod keyframer;
+mod limits;
 mod link_header;
 mod map;
 mod math;
 mod option;
+mod parallel;
 mod strings;
 
 pub fn calculate_padding(position: usize, alignment: usize) -> usize {
@@ -38,10 +43,22 @@ pub fn read_align_to<R: Read + Seek>(reader: &mut R, alignment: usize) -> BinRes
     reader.seek(SeekFrom::Current(padding as i64))?;
     Ok(())
 }
+
+// Readers must consume the whole BigFile
+pub fn expect_end<R: Read + Seek>(reader: &mut R) -> BffResult<()> {
+    let offset = reader.stream_position()?;
+    let len = reader.seek(SeekFrom::End(0))?;
+    if offset != len {
+        return Err(TrailingDataError::new(offset, len).into());
+    }
+    Ok(())
+}
 pub use dynarray::*;
 pub use keyframer::*;
+pub use limits::*;
 pub use link_header::*;
 pub use map::*;
 pub use math::*;
 pub use option::*;
+pub use parallel::*;
 pub use strings::*;
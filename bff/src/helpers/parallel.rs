@@ -0,0 +1,23 @@
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::bigfile::progress;
+use crate::helpers::ReadLimits;
+use crate::names::NameContext;
+
+// Run codec work on the rayon pool, the results are in the same order as the items. The read
+// limits, the name context and the progress observer of the calling thread are entered on the
+// worker threads.
+pub fn par_map<T: Send, U: Send>(items: Vec<T>, f: impl Fn(T) -> U + Send + Sync) -> Vec<U> {
+    let limits = ReadLimits::current();
+    let name_context = NameContext::current();
+    let observer = progress::current();
+    items
+        .into_par_iter()
+        .map(|item| {
+            let _limits = limits.enter();
+            let _names = name_context.enter();
+            let _progress = progress::observe(observer.clone());
+            f(item)
+        })
+        .collect()
+}
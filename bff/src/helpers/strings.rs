@@ -8,6 +8,8 @@ use binrw::{args, BinRead, BinResult, BinWrite, BinWriterExt, Endian, Error, Nul
 use derive_more::{Constructor, Deref, DerefMut, Display, Error, From, Into};
 use serde::{Deserialize, Serialize};
 
+use crate::helpers::check_count;
+
 #[derive(
     Clone,
     PartialEq,
@@ -125,7 +127,8 @@ impl BinRead for PascalString {
         endian: Endian,
         _: Self::Args<'_>,
     ) -> BinResult<Self> {
-        let count: usize = <u32>::read_options(reader, endian, ())? as usize;
+        let count = <u32>::read_options(reader, endian, ())? as usize;
+        let count = check_count(reader, count)?;
 
         let ascii_string_position = reader.stream_position()?;
 
@@ -189,7 +192,8 @@ impl BinRead for PascalStringNull {
         endian: Endian,
         _: Self::Args<'_>,
     ) -> BinResult<Self> {
-        let count: usize = <u32>::read_options(reader, endian, ())? as usize;
+        let count = <u32>::read_options(reader, endian, ())? as usize;
+        let count = check_count(reader, count)?;
 
         let begin = reader.stream_position()?;
 
@@ -197,7 +201,7 @@ impl BinRead for PascalStringNull {
             reader,
             endian,
             args! {
-                count: count - 1,
+                count: count.saturating_sub(1),
             },
         )?;
 
@@ -4,6 +4,7 @@ use arcode::bitbit::{BitReader, BitWriter, LSB};
 use arcode::{ArithmeticDecoder, ArithmeticEncoder, EOFKind, Model};
 use binrw::{args, BinRead, BinResult, BinWrite, Endian};
 
+use crate::helpers::check_decompressed_size;
 use crate::BffResult;
 
 #[binrw::writer(writer)]
@@ -58,6 +59,7 @@ pub fn arcode_decompress_data_parser(
     decompressed_size: u32,
     compressed_size: u32,
 ) -> BinResult<Vec<u8>> {
+    check_decompressed_size(reader, decompressed_size as usize)?;
     if compressed_size != 0 {
         let mut model = Model::builder()
             .num_symbols(256)
@@ -1,7 +1,11 @@
 use std::io::{Read, Seek, SeekFrom, Write};
 
 use binrw::{args, BinRead, BinReaderExt, BinResult, BinWrite, Endian};
+use lz4::block::CompressionMode;
+use serde::{Deserialize, Serialize};
 
+use crate::error::{CompressedSizeMismatchError, Lz4Error};
+use crate::helpers::check_decompressed_size;
 use crate::BffResult;
 
 #[binrw::parser(reader, endian)]
@@ -9,41 +13,93 @@ pub fn lz4_decompress_body_parser(
     decompressed_size: u32,
     compressed_size: u32,
 ) -> BinResult<Vec<u8>> {
+    let offset = reader.stream_position()?;
+
     // These fields are little endian even on big endian platforms.
     let read_decompressed_size = reader.read_le::<u32>()?;
     let read_compressed_size = reader.read_le::<u32>()?;
 
-    assert_eq!(decompressed_size, read_decompressed_size);
-    assert_eq!(compressed_size, read_compressed_size);
+    // Ensure the values from the object header match the values in the compressed data
+    for (field, expected, found) in [
+        ("decompressed", decompressed_size, read_decompressed_size),
+        ("compressed", compressed_size, read_compressed_size),
+    ] {
+        if expected != found {
+            return Err(binrw::Error::Custom {
+                pos: offset,
+                err: Box::new(CompressedSizeMismatchError::new(
+                    offset, field, expected, found,
+                )),
+            });
+        }
+    }
+
+    lz4_decompress_data_parser(
+        reader,
+        endian,
+        (decompressed_size, compressed_size.saturating_sub(8)),
+    )
+}
 
-    lz4_decompress_data_parser(reader, endian, (decompressed_size, compressed_size - 8))
+// Settings of the LZ4 compressor. The ones used by the tools of the game are not known, the ones
+// reproducing a stream of the game are found with lz4_detect_mode.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Lz4Mode {
+    #[default]
+    Default,
+    // Acceleration of the fast compressor
+    Fast(i32),
+    // Level of the high compression compressor
+    High(i32),
 }
 
-#[binrw::writer(writer)]
-pub fn lz4_compress_data_writer(data: &[u8]) -> BinResult<()> {
-    // TODO: Don't use asserts. Add proper error handling.
-    // TODO: Not the same parameters used by the game.
-    let compress_bound = unsafe { lz4::liblz4::LZ4_compressBound(data.len() as i32) as usize };
-    let mut compressed_buffer: Vec<u8> = Vec::with_capacity(compress_bound);
-    unsafe {
-        let result = lz4::liblz4::LZ4_compress_default(
-            data.as_ptr() as *const i8,
-            compressed_buffer.as_mut_ptr() as *mut i8,
-            data.len() as i32,
-            compressed_buffer.capacity() as i32,
-        );
-        assert!(result >= 0);
-        compressed_buffer.set_len(result as usize);
+impl Lz4Mode {
+    fn compress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mode = match self {
+            Self::Default => CompressionMode::DEFAULT,
+            Self::Fast(acceleration) => CompressionMode::FAST(acceleration),
+            Self::High(level) => CompressionMode::HIGHCOMPRESSION(level),
+        };
+        lz4::block::compress(data, Some(mode), false)
     }
-    writer.write_all(&compressed_buffer)?;
+}
+
+// The mode whose output is the compressed stream, without its size fields
+pub fn lz4_detect_mode(data: &[u8], compressed: &[u8]) -> Option<Lz4Mode> {
+    std::iter::once(Lz4Mode::Default)
+        .chain((1..=12).map(Lz4Mode::High))
+        .chain((2..=16).map(Lz4Mode::Fast))
+        .find(|mode| mode.compress(data).is_ok_and(|output| output == compressed))
+}
+
+#[binrw::writer(writer)]
+pub fn lz4_compress_data_writer(data: &[u8], mode: Lz4Mode) -> BinResult<()> {
+    writer.write_all(&mode.compress(data)?)?;
     Ok(())
 }
 
 #[binrw::writer(writer)]
-pub fn lz4_compress_data_with_header_writer_internal(data: &[u8]) -> BinResult<()> {
+pub fn lz4_compress_body_writer(data: &[u8], mode: Lz4Mode) -> BinResult<()> {
+    // Unlike the standalone header, the body header's compressed size includes the 8 bytes
+    // taken up by the size fields.
     let begin = writer.stream_position()?;
     writer.seek(SeekFrom::Current(8))?;
-    lz4_compress_data_writer(data, writer, Endian::Little, ())?;
+    lz4_compress_data_writer(data, writer, Endian::Little, (mode,))?;
+    let end = writer.stream_position()?;
+
+    // These fields are little endian even on big endian platforms.
+    writer.seek(SeekFrom::Start(begin))?;
+    (data.len() as u32).write_le(writer)?;
+    ((end - begin) as u32).write_le(writer)?;
+    writer.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+#[binrw::writer(writer)]
+pub fn lz4_compress_data_with_header_writer_internal(data: &[u8], mode: Lz4Mode) -> BinResult<()> {
+    let begin = writer.stream_position()?;
+    writer.seek(SeekFrom::Current(8))?;
+    lz4_compress_data_writer(data, writer, Endian::Little, (mode,))?;
     let end = writer.stream_position()?;
 
     writer.seek(SeekFrom::Start(begin))?;
@@ -62,7 +118,7 @@ pub fn lz4_compress_data_with_header_writer<W: Write + Seek>(
         data,
         writer,
         endian,
-        (),
+        (Lz4Mode::default(),),
     )?)
 }
 
@@ -71,7 +127,8 @@ pub fn lz4_decompress_data_parser(
     decompressed_size: u32,
     compressed_size: u32,
 ) -> BinResult<Vec<u8>> {
-    // TODO: Don't use asserts. Add proper error handling.
+    check_decompressed_size(reader, decompressed_size as usize)?;
+    let offset = reader.stream_position()?;
     if compressed_size != 0 {
         let compressed_buffer =
             Vec::<u8>::read_args(reader, args! { count: compressed_size as usize })?;
@@ -83,10 +140,25 @@ pub fn lz4_decompress_data_parser(
                 compressed_size as i32,
                 decompressed_size as i32,
             );
-            assert!(result >= 0);
+            if result < 0 {
+                return Err(binrw::Error::Custom {
+                    pos: offset,
+                    err: Box::new(Lz4Error::new(result)),
+                });
+            }
             decompressed_buffer.set_len(result as usize);
         }
-        assert_eq!(decompressed_buffer.len(), decompressed_size as usize);
+        if decompressed_buffer.len() != decompressed_size as usize {
+            return Err(binrw::Error::Custom {
+                pos: offset,
+                err: Box::new(CompressedSizeMismatchError::new(
+                    offset,
+                    "decompressed",
+                    decompressed_size,
+                    decompressed_buffer.len() as u32,
+                )),
+            });
+        }
 
         Ok(decompressed_buffer)
     } else {
@@ -113,3 +185,44 @@ pub fn lz4_decompress_data_with_header_parser<R: Read + Seek>(
         (),
     )?)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        (0..0x2000u32)
+            .flat_map(|i| [(i % 251) as u8, (i / 7) as u8, b'x'])
+            .collect()
+    }
+
+    #[test]
+    fn detected_mode_reproduces_the_stream() {
+        let data = sample();
+        for mode in [Lz4Mode::Default, Lz4Mode::High(9), Lz4Mode::Fast(4)] {
+            let compressed = mode.compress(&data).unwrap();
+            let detected = lz4_detect_mode(&data, &compressed).unwrap();
+            assert_eq!(detected.compress(&data).unwrap(), compressed);
+        }
+        assert_eq!(lz4_detect_mode(&data, b"not lz4"), None);
+    }
+
+    #[test]
+    fn compressed_data_round_trips() {
+        let data = sample();
+        let mut writer = Cursor::new(Vec::new());
+        lz4_compress_data_with_header_writer_internal(
+            &data,
+            &mut writer,
+            Endian::Little,
+            (Lz4Mode::High(12),),
+        )
+        .unwrap();
+        writer.set_position(0);
+        let decompressed =
+            lz4_decompress_data_with_header_parser(&mut writer, Endian::Little).unwrap();
+        assert_eq!(decompressed, data);
+    }
+}
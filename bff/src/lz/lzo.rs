@@ -3,6 +3,7 @@ use std::ptr::null_mut;
 
 use absperf_minilzo_sys::{lzo1x_1_compress, lzo1x_decompress_safe};
 
+use crate::error::LzoError;
 use crate::BffResult;
 
 pub fn lzo_compress<W: Write>(data: &[u8], writer: &mut W) -> BffResult<()> {
@@ -19,7 +20,9 @@ pub fn lzo_compress<W: Write>(data: &[u8], writer: &mut W) -> BffResult<()> {
             &mut compressed_len,
             wrkmem.as_mut_ptr() as *mut _,
         );
-        assert_eq!(result, 0);
+        if result != 0 {
+            return Err(LzoError::new(result).into());
+        }
 
         compressed.set_len(compressed_len as usize);
     }
@@ -40,7 +43,9 @@ pub fn lzo_decompress(compressed: &[u8], decompressed_buffer_size: usize) -> Bff
             &mut decompressed_len,
             null_mut(),
         );
-        assert_eq!(result, 0);
+        if result != 0 {
+            return Err(LzoError::new(result).into());
+        }
 
         decompressed.set_len(decompressed_len as usize);
     }
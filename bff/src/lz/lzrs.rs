@@ -1,9 +1,11 @@
 use std::cmp::{max, min};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 use std::ptr::null_mut;
 
 use binrw::{BinReaderExt, BinResult, BinWriterExt, Endian};
 
+use crate::error::CompressedSizeMismatchError;
+use crate::helpers::check_decompressed_size;
 use crate::BffResult;
 
 #[binrw::parser(reader, endian)]
@@ -11,6 +13,8 @@ pub fn lzrs_decompress_body_parser(
     decompressed_size: u32,
     compressed_size: u32,
 ) -> BinResult<Vec<u8>> {
+    let offset = reader.stream_position()?;
+
     // These fields are little endian even on big endian platforms.
     let read_decompressed_size = reader.read_le::<u32>()?;
     let read_compressed_size = reader.read_le::<u32>()?;
@@ -19,10 +23,25 @@ pub fn lzrs_decompress_body_parser(
     // in the compressed data.
     // compressed_size includes the 8 bytes taken up by the duplicate
     // size fields.
-    assert_eq!(decompressed_size, read_decompressed_size);
-    assert_eq!(compressed_size, read_compressed_size);
+    for (field, expected, found) in [
+        ("decompressed", decompressed_size, read_decompressed_size),
+        ("compressed", compressed_size, read_compressed_size),
+    ] {
+        if expected != found {
+            return Err(binrw::Error::Custom {
+                pos: offset,
+                err: Box::new(CompressedSizeMismatchError::new(
+                    offset, field, expected, found,
+                )),
+            });
+        }
+    }
 
-    lzrs_decompress_data_parser(reader, endian, (decompressed_size, compressed_size - 8))
+    lzrs_decompress_data_parser(
+        reader,
+        endian,
+        (decompressed_size, compressed_size.saturating_sub(8)),
+    )
 }
 
 #[binrw::parser(reader, endian)]
@@ -31,7 +50,11 @@ pub fn lzrs_decompress_data_with_header_parser_internal() -> BinResult<Vec<u8>>
     let decompressed_size = reader.read_le::<u32>()?;
     let compressed_size = reader.read_le::<u32>()?;
 
-    lzrs_decompress_data_parser(reader, endian, (decompressed_size, compressed_size - 8))
+    lzrs_decompress_data_parser(
+        reader,
+        endian,
+        (decompressed_size, compressed_size.saturating_sub(8)),
+    )
 }
 
 pub fn lzrs_decompress_data_with_header_parser<R: Read + Seek>(
@@ -50,6 +73,11 @@ pub fn lzrs_decompress_data_parser(
     decompressed_size: u32,
     _compressed_size: u32,
 ) -> BinResult<Vec<u8>> {
+    check_decompressed_size(reader, decompressed_size as usize)?;
+    // Nothing is written for empty data, not even flags
+    if decompressed_size == 0 {
+        return Ok(Vec::new());
+    }
     const WINDOW_LOG: u16 = 14;
     const WINDOW_MASK: u16 = (1 << WINDOW_LOG) - 1;
 
@@ -64,7 +92,15 @@ pub fn lzrs_decompress_data_parser(
         for _ in 0..30 {
             if (flags & 0x80000000) != 0 {
                 let temp = reader.read_be::<u16>()?;
-                let start = decompressed_buffer.len() - (temp & temp_mask) as usize - 1;
+                let Some(start) = decompressed_buffer
+                    .len()
+                    .checked_sub((temp & temp_mask) as usize + 1)
+                else {
+                    return Err(binrw::Error::AssertFail {
+                        pos: reader.stream_position()?,
+                        message: "LZRS match starts before the decompressed data".to_string(),
+                    });
+                };
                 let end = start + (temp >> temp_shift) as usize + 3;
 
                 // We don't want to use `extend_from_within` here because it requires the entire
@@ -87,7 +123,6 @@ pub fn lzrs_decompress_data_parser(
 
 #[binrw::writer(writer, endian)]
 pub fn lzrs_compress_data_with_header_writer_internal(data: &[u8]) -> BinResult<()> {
-    // println!("{:?}", data);
     let starting_position = writer.stream_position()?;
     let decompressed_size = data.len() as u32;
     writer.write_le::<u32>(&decompressed_size)?;
@@ -103,6 +138,13 @@ pub fn lzrs_compress_data_with_header_writer_internal(data: &[u8]) -> BinResult<
     Ok(())
 }
 
+// Compress into a buffer of its own so several bodies can be compressed at once
+pub fn lzrs_compress_data_with_header(data: &[u8], endian: Endian) -> BffResult<Vec<u8>> {
+    let mut writer = Cursor::new(Vec::new());
+    lzrs_compress_data_with_header_writer_internal(data, &mut writer, endian, ())?;
+    Ok(writer.into_inner())
+}
+
 pub fn lzrs_compress_data_with_header_writer<W: Write + Seek>(
     data: &[u8],
     writer: &mut W,
@@ -405,3 +447,61 @@ pub fn compress_data_writer(data: &[u8]) -> BinResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Deterministic bytes repeating earlier runs so both literals and matches are written
+    fn sample(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            if state & 3 == 0 && data.len() > 64 {
+                let start = (state as usize >> 8) % (data.len() - 32);
+                let run = 3 + (state as usize >> 4) % 29;
+                for i in 0..run.min(len - data.len()) {
+                    data.push(data[start + i]);
+                }
+            } else {
+                data.push(state as u8);
+            }
+        }
+        data
+    }
+
+    fn round_trip(data: &[u8]) {
+        let compressed = lzrs_compress_data_with_header(data, Endian::Little).unwrap();
+        assert_eq!(compressed[0..4], (data.len() as u32).to_le_bytes());
+        assert_eq!(compressed[4..8], (compressed.len() as u32).to_le_bytes());
+
+        let decompressed =
+            lzrs_decompress_data_with_header_parser(&mut Cursor::new(&compressed), Endian::Little)
+                .unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn short_data_round_trips() {
+        round_trip(&[]);
+        round_trip(&[42]);
+        round_trip(b"ab");
+        round_trip(b"abcabcabcabcabcabcabcabcabcabcabc");
+    }
+
+    #[test]
+    fn runs_round_trip() {
+        round_trip(&vec![0; 0x10000]);
+        round_trip(&b"0123456789".repeat(0x1000));
+    }
+
+    // Larger than the window so the match chains are refreshed while compressing
+    #[test]
+    fn data_larger_than_the_window_round_trips() {
+        round_trip(&sample(0x100, 1));
+        round_trip(&sample(0x30000, 2));
+    }
+}
@@ -4,6 +4,8 @@ use binrw::{args, BinRead, BinReaderExt, BinResult, BinWrite, Endian};
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
 
+use crate::error::CompressedSizeMismatchError;
+use crate::helpers::check_decompressed_size;
 use crate::BffResult;
 
 #[binrw::parser(reader, endian)]
@@ -11,32 +13,66 @@ pub fn zlib_decompress_body_parser(
     decompressed_size: u32,
     compressed_size: u32,
 ) -> BinResult<Vec<u8>> {
+    let offset = reader.stream_position()?;
+
     // These fields are little endian even on big endian platforms.
     let read_decompressed_size = reader.read_le::<u32>()?;
     let read_compressed_size = reader.read_le::<u32>()?;
 
-    assert_eq!(decompressed_size, read_decompressed_size);
-    assert_eq!(compressed_size - 8, read_compressed_size);
+    // Unlike LZ4 the compressed size in the data doesn't count the size fields
+    // Ensure the values from the object header match the values in the compressed data
+    for (field, expected, found) in [
+        ("decompressed", decompressed_size, read_decompressed_size),
+        (
+            "compressed",
+            compressed_size.saturating_sub(8),
+            read_compressed_size,
+        ),
+    ] {
+        if expected != found {
+            return Err(binrw::Error::Custom {
+                pos: offset,
+                err: Box::new(CompressedSizeMismatchError::new(
+                    offset, field, expected, found,
+                )),
+            });
+        }
+    }
 
-    zlib_decompress_data_parser(reader, endian, (decompressed_size, compressed_size - 8))
+    zlib_decompress_data_parser(
+        reader,
+        endian,
+        (decompressed_size, compressed_size.saturating_sub(8)),
+    )
 }
 
-#[binrw::writer(writer)]
-pub fn zlib_compress_data_writer(data: &[u8]) -> BinResult<()> {
-    // TODO: Don't use asserts. Add proper error handling.
-    // TODO: Not the same parameters used by the game.
-    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+// The level used unless another one is given. The level used by the tools of the game is not known,
+// the one reproducing a stream of the game is found with zlib_detect_level. Streams are only
+// reproduced if the zlib backend of flate2 makes the same choices as the one of the game.
+pub const ZLIB_DEFAULT_LEVEL: u32 = 9;
+
+fn zlib_compress(data: &[u8], level: u32) -> std::io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::new(level));
     encoder.write_all(data)?;
-    let compressed_buffer = encoder.finish()?;
-    writer.write_all(&compressed_buffer)?;
+    encoder.finish()
+}
+
+// The level whose output is the compressed stream, without its size fields
+pub fn zlib_detect_level(data: &[u8], compressed: &[u8]) -> Option<u32> {
+    (0..=9).find(|level| zlib_compress(data, *level).is_ok_and(|output| output == compressed))
+}
+
+#[binrw::writer(writer)]
+pub fn zlib_compress_data_writer(data: &[u8], level: u32) -> BinResult<()> {
+    writer.write_all(&zlib_compress(data, level)?)?;
     Ok(())
 }
 
 #[binrw::writer(writer)]
-pub fn zlib_compress_data_with_header_writer_internal(data: &[u8]) -> BinResult<()> {
+pub fn zlib_compress_data_with_header_writer_internal(data: &[u8], level: u32) -> BinResult<()> {
     let begin = writer.stream_position()?;
     writer.seek(SeekFrom::Current(8))?;
-    zlib_compress_data_writer(data, writer, Endian::Little, ())?;
+    zlib_compress_data_writer(data, writer, Endian::Little, (level,))?;
     let end = writer.stream_position()?;
 
     writer.seek(SeekFrom::Start(begin))?;
@@ -55,7 +91,7 @@ pub fn zlib_compress_data_with_header_writer<W: Write + Seek>(
         data,
         writer,
         endian,
-        (),
+        (ZLIB_DEFAULT_LEVEL,),
     )?)
 }
 
@@ -64,13 +100,24 @@ pub fn zlib_decompress_data_parser(
     decompressed_size: u32,
     compressed_size: u32,
 ) -> BinResult<Vec<u8>> {
-    // TODO: Don't use asserts. Add proper error handling.
+    check_decompressed_size(reader, decompressed_size as usize)?;
+    let offset = reader.stream_position()?;
     if compressed_size != 0 {
         let mut decoder = ZlibDecoder::new(reader.take(compressed_size as u64));
         let mut decompressed_buffer = Vec::with_capacity(decompressed_size as usize);
         decoder.read_to_end(&mut decompressed_buffer)?;
 
-        assert_eq!(decompressed_buffer.len(), decompressed_size as usize);
+        if decompressed_buffer.len() != decompressed_size as usize {
+            return Err(binrw::Error::Custom {
+                pos: offset,
+                err: Box::new(CompressedSizeMismatchError::new(
+                    offset,
+                    "decompressed",
+                    decompressed_size,
+                    decompressed_buffer.len() as u32,
+                )),
+            });
+        }
 
         Ok(decompressed_buffer)
     } else {
@@ -97,3 +144,38 @@ pub fn zlib_decompress_data_with_header_parser<R: Read + Seek>(
         (),
     )?)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        (0..0x2000u32)
+            .flat_map(|i| [(i % 251) as u8, (i / 7) as u8, b'x'])
+            .collect()
+    }
+
+    #[test]
+    fn detected_level_reproduces_the_stream() {
+        let data = sample();
+        for level in [1, 6, ZLIB_DEFAULT_LEVEL] {
+            let compressed = zlib_compress(&data, level).unwrap();
+            let detected = zlib_detect_level(&data, &compressed).unwrap();
+            assert_eq!(zlib_compress(&data, detected).unwrap(), compressed);
+        }
+        assert_eq!(zlib_detect_level(&data, b"not zlib"), None);
+    }
+
+    #[test]
+    fn compressed_data_round_trips() {
+        let data = sample();
+        let mut writer = Cursor::new(Vec::new());
+        zlib_compress_data_with_header_writer(&data, &mut writer, Endian::Little).unwrap();
+        writer.set_position(0);
+        let decompressed =
+            zlib_decompress_data_with_header_parser(&mut writer, Endian::Little).unwrap();
+        assert_eq!(decompressed, data);
+    }
+}
@@ -43,6 +43,7 @@ macro_rules! classes {
 
             fn try_from_version_platform(object: &crate::bigfile::resource::Resource, version: crate::bigfile::versions::Version, platform: crate::bigfile::platforms::Platform) -> crate::BffResult<Class> {
                 use crate::traits::NamedClass;
+                let _names = crate::names::NameContext::current().with_name_type((&version).try_into()?).enter();
                 match object.class_name {
                     $(crate::names::Name::Asobo32($class::NAME) | crate::names::Name::Asobo32($class::NAME_LEGACY)
                     | crate::names::Name::AsoboAlternate32($class::NAME) | crate::names::Name::AsoboAlternate32($class::NAME_LEGACY)
@@ -60,6 +61,7 @@ macro_rules! classes {
 
             fn try_from_version_platform(class: &Class, version: crate::bigfile::versions::Version, platform: crate::bigfile::platforms::Platform) -> crate::BffResult<crate::bigfile::resource::Resource> {
                 use std::ops::Deref;
+                let _names = crate::names::NameContext::current().with_name_type((&version).try_into()?).enter();
                 match class {
                     $(Class::$class(class) => Ok(<&$class as crate::traits::TryIntoVersionPlatform<crate::bigfile::resource::Resource>>::try_into_version_platform(class.deref(), version, platform)?),)*
                 }
@@ -78,6 +78,17 @@ macro_rules! platforms {
                 }
             }
         }
+
+        impl std::str::FromStr for Platform {
+            type Err = crate::error::Error;
+
+            fn from_str(platform: &str) -> Result<Self, Self::Err> {
+                match platform {
+                    $(platform if platform.eq_ignore_ascii_case(stringify!($platform)) => Ok(Platform::$platform),)*
+                    _ => Err(crate::error::InvalidPlatformError::new(platform.to_string()).into()),
+                }
+            }
+        }
     };
 }
 
@@ -1,19 +1,27 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter, Write as _};
 use std::hash::Hash;
 use std::io::{BufRead, Read, Seek, Write};
-use std::sync::Mutex;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
 
 use binrw::{BinRead, BinResult, BinWrite, Endian};
 use derive_more::{Display, From};
 use encoding_rs::WINDOWS_1252;
-use once_cell::sync::Lazy;
+use serde::de::DeserializeSeed;
 use serde::{Deserialize, Deserializer, Serialize};
 use string_interner::{DefaultSymbol, StringInterner};
 
 use crate::class::class_names;
 use crate::crc::{Asobo32, Asobo64, AsoboAlternate32, BlackSheep32, Kalisto32, Ubisoft64};
+use crate::error::{
+    InvalidNameLineError,
+    InvalidNamesEncodingError,
+    NameHashMismatchError,
+    UnknownNamesError,
+};
 use crate::traits::NameHashFunction;
 use crate::BffResult;
 
@@ -63,7 +71,7 @@ impl BinRead for Name {
         endian: Endian,
         _args: Self::Args<'_>,
     ) -> BinResult<Self> {
-        match names().lock().unwrap().name_type {
+        match NameContext::current_name_type() {
             NameType::Asobo32 => NameAsobo32::read_options(reader, endian, ()).map(Name::Asobo32),
             NameType::AsoboAlternate32 => {
                 NameAsoboAlternate32::read_options(reader, endian, ()).map(Name::AsoboAlternate32)
@@ -91,27 +99,30 @@ impl BinWrite for Name {
         endian: Endian,
         _args: Self::Args<'_>,
     ) -> BinResult<()> {
-        let name_type = names().lock().unwrap().name_type;
+        let name = NameContext::convert_current(self).map_err(|err| binrw::Error::Custom {
+            pos: writer.stream_position().unwrap_or_default(),
+            err: Box::new(err),
+        })?;
+        match name {
+            Name::Asobo32(name) => name.write_options(writer, endian, ()),
+            Name::AsoboAlternate32(name) => name.write_options(writer, endian, ()),
+            Name::Kalisto32(name) => name.write_options(writer, endian, ()),
+            Name::BlackSheep32(name) => name.write_options(writer, endian, ()),
+            Name::Asobo64(name) => name.write_options(writer, endian, ()),
+            Name::Ubisoft64(name) => name.write_options(writer, endian, ()),
+        }
+    }
+}
+
+impl Name {
+    pub fn name_type(&self) -> NameType {
         match self {
-            Name::Asobo32(name) if name_type == NameType::Asobo32 => {
-                name.write_options(writer, endian, ())
-            }
-            Name::AsoboAlternate32(name) if name_type == NameType::AsoboAlternate32 => {
-                name.write_options(writer, endian, ())
-            }
-            Name::Kalisto32(name) if name_type == NameType::Kalisto32 => {
-                name.write_options(writer, endian, ())
-            }
-            Name::BlackSheep32(name) if name_type == NameType::BlackSheep32 => {
-                name.write_options(writer, endian, ())
-            }
-            Name::Asobo64(name) if name_type == NameType::Asobo64 => {
-                name.write_options(writer, endian, ())
-            }
-            Name::Ubisoft64(name) if name_type == NameType::Ubisoft64 => {
-                name.write_options(writer, endian, ())
-            }
-            _ => todo!("Cannot convert between name types"),
+            Name::Asobo32(_) => NameType::Asobo32,
+            Name::AsoboAlternate32(_) => NameType::AsoboAlternate32,
+            Name::Kalisto32(_) => NameType::Kalisto32,
+            Name::BlackSheep32(_) => NameType::BlackSheep32,
+            Name::Asobo64(_) => NameType::Asobo64,
+            Name::Ubisoft64(_) => NameType::Ubisoft64,
         }
     }
 }
@@ -126,11 +137,88 @@ pub enum NameType {
     Ubisoft64,
 }
 
+impl NameType {
+    pub fn hash(self, bytes: &[u8]) -> Name {
+        match self {
+            NameType::Asobo32 => NameAsobo32::hash(bytes).into(),
+            NameType::AsoboAlternate32 => NameAsoboAlternate32::hash(bytes).into(),
+            NameType::Kalisto32 => NameKalisto32::hash(bytes).into(),
+            NameType::BlackSheep32 => NameBlackSheep32::hash(bytes).into(),
+            NameType::Asobo64 => NameAsobo64::hash(bytes).into(),
+            NameType::Ubisoft64 => NameUbisoft64::hash(bytes).into(),
+        }
+    }
+}
+
+// How names are serialized, both forms can be deserialized
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum NameFormat {
+    // The string of the name when it is known, its hash otherwise
+    #[default]
+    StringOrHash,
+    // {"hash": 123, "string": "foo"} so the name does not depend on the strings known when it is
+    // deserialized, the string is left out when it is not known
+    HashAndString,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(untagged)]
-enum SerdeName<'a, T> {
+enum SerdeName<T> {
     Name(T),
-    String(&'a str),
+    String(String),
+    HashAndString {
+        hash: T,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        string: Option<String>,
+    },
+}
+
+impl<T> SerdeName<T> {
+    fn hash(hash: T, string: Option<String>) -> Self {
+        match NameContext::current_name_format() {
+            NameFormat::StringOrHash => SerdeName::Name(hash),
+            NameFormat::HashAndString => SerdeName::HashAndString { hash, string },
+        }
+    }
+}
+
+fn deserialize_variant<'de, H: NameHashFunction, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Name, D::Error>
+where
+    for<'a> H::Target: PartialEq
+        + Eq
+        + Hash
+        + Copy
+        + Clone
+        + BinRead
+        + BinWrite<Args<'a> = ()>
+        + Display
+        + Debug
+        + Deserialize<'de>,
+    for<'a> <H::Target as BinRead>::Args<'a>: Default,
+    NameVariant<H>: Into<Name>,
+{
+    match SerdeName::deserialize(deserializer)? {
+        SerdeName::Name(hash) => Ok(NameVariant::<H>::new(hash).into()),
+        SerdeName::String(string) => Ok(NameVariant::<H>::from(string.as_str()).into()),
+        SerdeName::HashAndString { hash, string } => {
+            let name: Name = NameVariant::<H>::new(hash).into();
+            if let Some(string) = string {
+                // The string must belong to the hash, it is only learned once it does
+                let hashed: Name = NameVariant::<H>::hash(string.as_bytes()).into();
+                if hashed != name {
+                    return Err(serde::de::Error::custom(NameHashMismatchError::new(
+                        name.name_type(),
+                        name,
+                        string,
+                    )));
+                }
+                NameContext::with_current_names(|names| names.write().unwrap().insert(&string));
+            }
+            Ok(name)
+        }
+    }
 }
 
 impl<H: NameHashFunction> From<&str> for NameVariant<H>
@@ -140,7 +228,7 @@ where
     for<'a> <H::Target as BinRead>::Args<'a>: Default,
 {
     fn from(value: &str) -> Self {
-        NAMES.lock().unwrap().insert(value);
+        NameContext::with_current_names(|names| names.write().unwrap().insert(value));
         Self(H::hash(value.as_bytes()))
     }
 }
@@ -158,7 +246,7 @@ where
 
 impl Default for Name {
     fn default() -> Self {
-        match names().lock().unwrap().name_type {
+        match NameContext::current_name_type() {
             NameType::Asobo32 => NameAsobo32::default().into(),
             NameType::AsoboAlternate32 => NameAsoboAlternate32::default().into(),
             NameType::Kalisto32 => NameKalisto32::default().into(),
@@ -171,30 +259,22 @@ impl Default for Name {
 
 impl Serialize for Name {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let name_type = names().lock().unwrap().name_type;
-        match NAMES.lock().unwrap().get(self) {
-            Some(name) => name.serialize(serializer),
-            None => match self {
-                Name::Asobo32(name) if name_type == NameType::Asobo32 => {
-                    name.0.serialize(serializer)
-                }
-                Name::AsoboAlternate32(name) if name_type == NameType::AsoboAlternate32 => {
-                    name.0.serialize(serializer)
-                }
-                Name::Kalisto32(name) if name_type == NameType::Kalisto32 => {
-                    name.0.serialize(serializer)
-                }
-                Name::BlackSheep32(name) if name_type == NameType::BlackSheep32 => {
-                    name.0.serialize(serializer)
-                }
-                Name::Asobo64(name) if name_type == NameType::Asobo64 => {
-                    name.0.serialize(serializer)
-                }
-                Name::Ubisoft64(name) if name_type == NameType::Ubisoft64 => {
-                    name.0.serialize(serializer)
-                }
-                _ => todo!("Cannot convert between name types"),
-            },
+        let string = NameContext::with_current_names(|names| {
+            names.read().unwrap().get(self).map(str::to_owned)
+        });
+        if let (NameFormat::StringOrHash, Some(name)) =
+            (NameContext::current_name_format(), &string)
+        {
+            return name.serialize(serializer);
+        }
+
+        match NameContext::convert_current(self).map_err(serde::ser::Error::custom)? {
+            Name::Asobo32(name) => SerdeName::hash(name.0, string).serialize(serializer),
+            Name::AsoboAlternate32(name) => SerdeName::hash(name.0, string).serialize(serializer),
+            Name::Kalisto32(name) => SerdeName::hash(name.0, string).serialize(serializer),
+            Name::BlackSheep32(name) => SerdeName::hash(name.0, string).serialize(serializer),
+            Name::Asobo64(name) => SerdeName::hash(name.0, string).serialize(serializer),
+            Name::Ubisoft64(name) => SerdeName::hash(name.0, string).serialize(serializer),
         }
     }
 }
@@ -204,56 +284,23 @@ impl<'de> Deserialize<'de> for Name {
     where
         D: Deserializer<'de>,
     {
-        match names().lock().unwrap().name_type {
-            NameType::Asobo32 => {
-                let serde_name = SerdeName::deserialize(deserializer)?;
-                match serde_name {
-                    SerdeName::Name(name) => Ok(NameAsobo32::new(name).into()),
-                    SerdeName::String(string) => Ok(NameAsobo32::from(string).into()),
-                }
-            }
-            NameType::AsoboAlternate32 => {
-                let serde_name = SerdeName::deserialize(deserializer)?;
-                match serde_name {
-                    SerdeName::Name(name) => Ok(NameAsoboAlternate32::new(name).into()),
-                    SerdeName::String(string) => Ok(NameAsoboAlternate32::from(string).into()),
-                }
-            }
-            NameType::Kalisto32 => {
-                let serde_name = SerdeName::deserialize(deserializer)?;
-                match serde_name {
-                    SerdeName::Name(name) => Ok(NameKalisto32::new(name).into()),
-                    SerdeName::String(string) => Ok(NameKalisto32::from(string).into()),
-                }
-            }
-            NameType::BlackSheep32 => {
-                let serde_name = SerdeName::deserialize(deserializer)?;
-                match serde_name {
-                    SerdeName::Name(name) => Ok(NameBlackSheep32::new(name).into()),
-                    SerdeName::String(string) => Ok(NameBlackSheep32::from(string).into()),
-                }
-            }
-            NameType::Asobo64 => {
-                let serde_name = SerdeName::deserialize(deserializer)?;
-                match serde_name {
-                    SerdeName::Name(name) => Ok(NameAsobo64::new(name).into()),
-                    SerdeName::String(string) => Ok(NameAsobo64::from(string).into()),
-                }
-            }
-            NameType::Ubisoft64 => {
-                let serde_name = SerdeName::deserialize(deserializer)?;
-                match serde_name {
-                    SerdeName::Name(name) => Ok(NameUbisoft64::new(name).into()),
-                    SerdeName::String(string) => Ok(NameUbisoft64::from(string).into()),
-                }
-            }
+        match NameContext::current_name_type() {
+            NameType::Asobo32 => deserialize_variant::<Asobo32, D>(deserializer),
+            NameType::AsoboAlternate32 => deserialize_variant::<AsoboAlternate32, D>(deserializer),
+            NameType::Kalisto32 => deserialize_variant::<Kalisto32, D>(deserializer),
+            NameType::BlackSheep32 => deserialize_variant::<BlackSheep32, D>(deserializer),
+            NameType::Asobo64 => deserialize_variant::<Asobo64, D>(deserializer),
+            NameType::Ubisoft64 => deserialize_variant::<Ubisoft64, D>(deserializer),
         }
     }
 }
 
 impl Display for Name {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        if let Some(name) = names().lock().unwrap().get(self) {
+        let string = NameContext::with_current_names(|names| {
+            names.read().unwrap().get(self).map(str::to_owned)
+        });
+        if let Some(name) = string {
             write!(f, "{}", name)
         } else {
             match self {
@@ -270,7 +317,10 @@ impl Display for Name {
 
 impl Debug for Name {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        if let Some(name) = names().lock().unwrap().get(self) {
+        let string = NameContext::with_current_names(|names| {
+            names.read().unwrap().get(self).map(str::to_owned)
+        });
+        if let Some(name) = string {
             write!(f, r#""{}""#, name)
         } else {
             match self {
@@ -287,7 +337,6 @@ impl Debug for Name {
 
 #[derive(Debug)]
 pub struct Names {
-    pub name_type: NameType,
     strings: StringInterner,
     asobo32_names: HashMap<NameAsobo32, DefaultSymbol>,
     asobo_alternate32_names: HashMap<NameAsoboAlternate32, DefaultSymbol>,
@@ -322,7 +371,24 @@ impl Names {
             .or_insert(sym);
     }
 
-    fn get(&self, name: &Name) -> Option<&str> {
+    // Add the strings of another table
+    pub fn extend(&mut self, other: &Names) {
+        for (_, string) in &other.strings {
+            self.insert(string);
+        }
+    }
+
+    // Re-hash a name with another hash function through its string
+    pub fn convert(&self, name: &Name, name_type: NameType) -> Option<Name> {
+        if name.name_type() == name_type {
+            Some(*name)
+        } else {
+            self.get(name)
+                .map(|string| name_type.hash(string.as_bytes()))
+        }
+    }
+
+    pub fn get(&self, name: &Name) -> Option<&str> {
         match name {
             Name::Asobo32(n) => self
                 .asobo32_names
@@ -355,7 +421,6 @@ impl Names {
 impl Default for Names {
     fn default() -> Self {
         let mut names = Self {
-            name_type: NameType::Asobo32,
             strings: StringInterner::default(),
             asobo32_names: Default::default(),
             asobo_alternate32_names: Default::default(),
@@ -375,14 +440,167 @@ impl Default for Names {
     }
 }
 
-// TODO: This should NOT be a global. It should be passed around as a parameter to the serialize
-// and deserialize functions. Doing that with derive is a bit tricky though.
-// https://docs.rs/serde_state/latest/serde_state/ outdated.
-// Until this is done bff is not thread safe.
-static NAMES: Lazy<Mutex<Names>> = Lazy::new(|| Mutex::new(Names::default()));
+// The hash type and string table used to read, write, and display names. Every BigFile carries
+// the context it was read with, readers take it as a parameter and serde goes through
+// NameContext::serialize and NameContext::deserialize. The derived BinRead/BinWrite/serde impls of
+// the classes cannot take it as a parameter, so those entry points enter it on the current thread
+// for as long as they run.
+#[derive(Debug, Clone)]
+pub struct NameContext {
+    pub name_type: NameType,
+    pub names: Arc<RwLock<Names>>,
+    pub name_format: NameFormat,
+}
+
+impl Default for NameContext {
+    fn default() -> Self {
+        Self::new(NameType::Asobo32)
+    }
+}
+
+thread_local! {
+    static NAME_CONTEXT: RefCell<NameContext> = RefCell::new(NameContext::default());
+}
+
+impl NameContext {
+    pub fn new(name_type: NameType) -> Self {
+        Self {
+            name_type,
+            names: Arc::new(RwLock::new(Names::default())),
+            name_format: NameFormat::default(),
+        }
+    }
+
+    pub fn with_name_type(&self, name_type: NameType) -> Self {
+        Self {
+            name_type,
+            names: Arc::clone(&self.names),
+            name_format: self.name_format,
+        }
+    }
+
+    pub fn with_name_format(&self, name_format: NameFormat) -> Self {
+        Self {
+            name_type: self.name_type,
+            names: Arc::clone(&self.names),
+            name_format,
+        }
+    }
 
-pub fn names() -> &'static Mutex<Names> {
-    &NAMES
+    // The context entered on the current thread
+    pub(crate) fn current() -> Self {
+        NAME_CONTEXT.with(|context| context.borrow().clone())
+    }
+
+    // Make this the current thread's context until the guard is dropped
+    pub fn enter(&self) -> NameContextGuard {
+        let previous = NAME_CONTEXT.with(|context| context.replace(self.clone()));
+        NameContextGuard {
+            previous: Some(previous),
+            _not_send: PhantomData,
+        }
+    }
+
+    // Serialize a value whose names are written with this context
+    pub fn serialize<'a, T: Serialize + ?Sized>(&'a self, value: &'a T) -> WithNames<'a, &'a T> {
+        WithNames {
+            name_context: self,
+            value,
+        }
+    }
+
+    // Deserialize a value whose names are read with this context, strings found in the value are
+    // added to its names
+    pub fn deserialize<T>(&self) -> WithNames<'_, PhantomData<T>> {
+        WithNames {
+            name_context: self,
+            value: PhantomData,
+        }
+    }
+
+    // Convert names to this context's hash type, every name without a known string is listed in the
+    // error
+    pub fn convert_all(
+        &self,
+        names: impl IntoIterator<Item = Name>,
+    ) -> Result<Vec<Name>, UnknownNamesError> {
+        let table = self.names.read().unwrap();
+        let mut converted = Vec::new();
+        let mut unknown = Vec::new();
+
+        for name in names {
+            match table.convert(&name, self.name_type) {
+                Some(name) => converted.push(name),
+                None if !unknown.contains(&name) => unknown.push(name),
+                None => {}
+            }
+        }
+
+        if unknown.is_empty() {
+            Ok(converted)
+        } else {
+            Err(UnknownNamesError::new(self.name_type, unknown))
+        }
+    }
+
+    fn convert_current(name: &Name) -> Result<Name, UnknownNamesError> {
+        let context = Self::current();
+        let converted = context
+            .names
+            .read()
+            .unwrap()
+            .convert(name, context.name_type);
+        converted.ok_or_else(|| UnknownNamesError::new(context.name_type, vec![*name]))
+    }
+
+    fn current_name_type() -> NameType {
+        NAME_CONTEXT.with(|context| context.borrow().name_type)
+    }
+
+    fn current_name_format() -> NameFormat {
+        NAME_CONTEXT.with(|context| context.borrow().name_format)
+    }
+
+    fn with_current_names<T>(f: impl FnOnce(&RwLock<Names>) -> T) -> T {
+        let names = NAME_CONTEXT.with(|context| Arc::clone(&context.borrow().names));
+        f(&names)
+    }
+}
+
+// Serde has no state to carry the context, so it is entered around the serialization
+pub struct WithNames<'a, T> {
+    name_context: &'a NameContext,
+    value: T,
+}
+
+impl<T: Serialize + ?Sized> Serialize for WithNames<'_, &T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let _names = self.name_context.enter();
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for WithNames<'_, PhantomData<T>> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        let _names = self.name_context.enter();
+        T::deserialize(deserializer)
+    }
+}
+
+pub struct NameContextGuard {
+    previous: Option<NameContext>,
+    // The guard restores the context of the thread it was created on
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for NameContextGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            NAME_CONTEXT.with(|context| *context.borrow_mut() = previous);
+        }
+    }
 }
 
 impl Names {
@@ -390,13 +608,16 @@ impl Names {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes)?;
 
-        let (cow, encoding_used, had_errors) = WINDOWS_1252.decode(&bytes);
-        // TODO: Handle errors
-        assert_eq!(encoding_used, WINDOWS_1252);
-        assert!(!had_errors, "Name decoding failed");
+        // A byte order mark switches the decoder to UTF-8 or UTF-16, which is fine
+        let (cow, _, had_errors) = WINDOWS_1252.decode(&bytes);
+        if had_errors {
+            return Err(InvalidNamesEncodingError.into());
+        }
 
         for line in cow.lines() {
-            let (_, string) = line.split_once(' ').unwrap();
+            let Some((_, string)) = line.split_once(' ') else {
+                return Err(InvalidNameLineError::new(line.to_owned()).into());
+            };
             let string = string.trim_matches('"');
             self.insert(string);
         }
@@ -404,9 +625,9 @@ impl Names {
         Ok(())
     }
 
-    pub fn write<W: Write>(&self, writer: &mut W) -> BffResult<()> {
+    pub fn write<W: Write>(&self, writer: &mut W, name_type: NameType) -> BffResult<()> {
         let mut out = String::new();
-        match self.name_type {
+        match name_type {
             NameType::Asobo32 => {
                 for (_, string) in &self.strings {
                     writeln!(
@@ -469,13 +690,94 @@ impl Names {
             }
         }
 
-        let (cow, encoding_used, had_errors) = WINDOWS_1252.encode(&out);
-        // TODO: Handle errors
-        assert_eq!(encoding_used, WINDOWS_1252);
-        assert!(!had_errors, "Name encoding failed");
+        let (cow, _, had_errors) = WINDOWS_1252.encode(&out);
+        if had_errors {
+            return Err(InvalidNamesEncodingError.into());
+        }
 
         writer.write_all(&cow)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deserialize(name_context: &NameContext, json: &str) -> serde_json::Result<Name> {
+        name_context
+            .deserialize::<Name>()
+            .deserialize(&mut serde_json::Deserializer::from_str(json))
+    }
+
+    #[test]
+    fn names_are_converted_through_their_string() {
+        let mut names = Names::default();
+        names.insert("foo");
+
+        let asobo32 = NameType::Asobo32.hash(b"foo");
+        let asobo64 = NameType::Asobo64.hash(b"foo");
+        assert_eq!(names.convert(&asobo32, NameType::Asobo64), Some(asobo64));
+        assert_eq!(names.convert(&asobo64, NameType::Asobo32), Some(asobo32));
+        assert_eq!(names.convert(&asobo32, NameType::Asobo32), Some(asobo32));
+
+        let unknown = NameType::Asobo32.hash(b"bar");
+        assert_eq!(names.convert(&unknown, NameType::Asobo64), None);
+    }
+
+    #[test]
+    fn unknown_names_are_listed_once() {
+        let name_context = NameContext::new(NameType::Asobo64);
+        name_context.names.write().unwrap().insert("foo");
+
+        let foo = NameType::Asobo32.hash(b"foo");
+        let bar = NameType::Asobo32.hash(b"bar");
+        let err = name_context.convert_all([foo, bar, bar]).unwrap_err();
+        assert_eq!(err.name_type, NameType::Asobo64);
+        assert_eq!(err.names, vec![bar]);
+
+        assert_eq!(
+            name_context.convert_all([foo]).unwrap(),
+            vec![NameType::Asobo64.hash(b"foo")]
+        );
+    }
+
+    #[test]
+    fn hash_and_string_round_trips() {
+        let name_context = NameContext::default().with_name_format(NameFormat::HashAndString);
+        let foo = NameType::Asobo32.hash(b"foo");
+        name_context.names.write().unwrap().insert("foo");
+
+        let json = serde_json::to_string(&name_context.serialize(&foo)).unwrap();
+        let Name::Asobo32(hash) = foo else {
+            unreachable!()
+        };
+        assert_eq!(json, format!(r#"{{"hash":{},"string":"foo"}}"#, hash.0));
+
+        // A fresh table learns the string from the name
+        let fresh = NameContext::default();
+        assert_eq!(deserialize(&fresh, &json).unwrap(), foo);
+        assert_eq!(fresh.names.read().unwrap().get(&foo), Some("foo"));
+    }
+
+    #[test]
+    fn hash_and_string_must_agree() {
+        let name_context = NameContext::default();
+        let Name::Asobo32(hash) = NameType::Asobo32.hash(b"foo") else {
+            unreachable!()
+        };
+
+        let json = format!(r#"{{"hash":{},"string":"bar"}}"#, hash.0);
+        assert!(deserialize(&name_context, &json).is_err());
+        let bar = NameType::Asobo32.hash(b"bar");
+        assert_eq!(name_context.names.read().unwrap().get(&bar), None);
+
+        // Without a string the hash is taken as is
+        let json = format!(r#"{{"hash":{}}}"#, hash.0);
+        assert_eq!(
+            deserialize(&name_context, &json).unwrap(),
+            NameType::Asobo32.hash(b"foo")
+        );
+    }
+}
@@ -1,9 +1,16 @@
 use std::io::{Read, Seek, Write};
 
+use crate::bigfile::layout::DEFAULT_MAX_BLOCK_SIZE;
+use crate::bigfile::lazy::BigFileIndex;
 use crate::bigfile::platforms::Platform;
+use crate::bigfile::resource::Resource;
+use crate::bigfile::salvage::SalvagedBigFile;
+use crate::bigfile::verify::ChecksumMismatch;
 use crate::bigfile::versions::Version;
 use crate::bigfile::BigFile;
+use crate::error::{SalvageUnsupportedError, UnimplementedVersionPlatformError};
 use crate::names::NameType;
+use crate::traits::SetLen;
 use crate::BffResult;
 
 pub trait BigFileIo {
@@ -13,13 +20,56 @@ pub trait BigFileIo {
         platform: Platform,
     ) -> BffResult<BigFile>;
 
+    fn read_index<R: Read + Seek>(
+        reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<BigFileIndex> {
+        Ok(Self::read(reader, version, platform)?.into())
+    }
+
+    // Read as much of a damaged BigFile as possible, blocks that fail to parse are skipped and
+    // listed alongside the objects that could be read. Versions that cannot skip a block refuse
+    // to salvage rather than read strictly.
+    fn read_salvage<R: Read + Seek>(
+        _reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<SalvagedBigFile> {
+        Err(SalvageUnsupportedError::new(version, platform).into())
+    }
+
     fn write<W: Write + Seek>(
         bigfile: &BigFile,
         writer: &mut W,
         tag: Option<&str>,
     ) -> BffResult<()>;
 
+    // Replace a resource in an existing BigFile, rewriting as little as possible. The stream is
+    // cut to the new length of the BigFile, which is returned.
+    fn patch<S: Read + Write + Seek + SetLen>(
+        _stream: &mut S,
+        version: Version,
+        platform: Platform,
+        _resource: &Resource,
+    ) -> BffResult<u64> {
+        Err(UnimplementedVersionPlatformError::new(version, platform).into())
+    }
+
+    // Recompute the checksum of every block that has one and list the ones that do not match
+    fn verify<R: Read + Seek>(
+        _reader: &mut R,
+        version: Version,
+        platform: Platform,
+    ) -> BffResult<Vec<ChecksumMismatch>> {
+        Err(UnimplementedVersionPlatformError::new(version, platform).into())
+    }
+
     const NAME_TYPE: NameType;
 
+    // Largest working buffer a block planned by BigFile::plan_layout or BigFile::merge may need,
+    // versions whose games have a known limit set it here
+    const MAX_BLOCK_SIZE: usize = DEFAULT_MAX_BLOCK_SIZE;
+
     type ResourceType;
 }
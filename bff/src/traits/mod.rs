@@ -3,6 +3,7 @@ mod export;
 mod name_hash_function;
 mod named_class;
 mod referenced_names;
+mod set_len;
 mod try_version_platform;
 
 pub use big_file_io::*;
@@ -10,4 +11,5 @@ pub use export::*;
 pub use name_hash_function::*;
 pub use named_class::*;
 pub use referenced_names::*;
+pub use set_len::*;
 pub use try_version_platform::*;
@@ -0,0 +1,27 @@
+use std::fs::File;
+use std::io::{Cursor, Result};
+
+// Streams that can be cut short, patching a block that shrinks moves the end of the BigFile back
+pub trait SetLen {
+    fn set_len(&mut self, len: u64) -> Result<()>;
+}
+
+impl SetLen for File {
+    fn set_len(&mut self, len: u64) -> Result<()> {
+        File::set_len(self, len)
+    }
+}
+
+impl SetLen for Cursor<Vec<u8>> {
+    fn set_len(&mut self, len: u64) -> Result<()> {
+        self.get_mut().truncate(len as usize);
+        Ok(())
+    }
+}
+
+impl SetLen for Cursor<&mut Vec<u8>> {
+    fn set_len(&mut self, len: u64) -> Result<()> {
+        self.get_mut().truncate(len as usize);
+        Ok(())
+    }
+}
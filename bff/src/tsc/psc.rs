@@ -12,6 +12,7 @@ use crate::lz::{
     gzip_decompress_data_with_header_parser_internal,
     lz4_compress_data_with_header_writer_internal,
     lz4_decompress_data_with_header_parser_internal,
+    Lz4Mode,
 };
 use crate::BffResult;
 
@@ -129,7 +130,7 @@ impl Psc {
                     &psc_data.into_inner(),
                     writer,
                     Endian::Little,
-                    (),
+                    (Lz4Mode::default(),),
                 )?;
             }
             PscAlgorithm::Gzip => {
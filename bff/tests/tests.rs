@@ -4,14 +4,18 @@
 #[cfg(test)]
 mod tests {
     use std::fs::File;
+    use std::io::Cursor;
     use std::path::PathBuf;
 
     use bff::bigfile::platforms::Platform;
     use bff::bigfile::resource::Resource;
     use bff::bigfile::BigFile;
     use bff::class::Class;
+    use bff::lz::{lzrs_compress_data_with_header, lzrs_decompress_data_with_header_parser};
+    use bff::names::NameContext;
     use bff::traits::TryIntoVersionPlatform;
     use binrw::io::BufReader;
+    use binrw::Endian;
 
     #[datatest::data("../data/read.yaml")]
     #[test]
@@ -23,7 +27,8 @@ mod tests {
         };
         let f = File::open(bigfile_path).unwrap();
         let mut reader = BufReader::new(f);
-        let _ = BigFile::read_platform(&mut reader, platform).unwrap();
+        let _ =
+            BigFile::read_platform(&mut reader, platform, &NameContext::default(), None).unwrap();
     }
 
     #[datatest::data("../data/roundtrip_objects.yaml")]
@@ -36,7 +41,8 @@ mod tests {
         };
         let f = File::open(bigfile_path).unwrap();
         let mut reader = BufReader::new(f);
-        let bigfile = BigFile::read_platform(&mut reader, platform).unwrap();
+        let bigfile =
+            BigFile::read_platform(&mut reader, platform, &NameContext::default(), None).unwrap();
 
         for object in bigfile.objects.values() {
             let class: Class = object
@@ -50,4 +56,16 @@ mod tests {
             assert_eq!(new_object, *object);
         }
     }
+
+    #[datatest::data("../data/lzrs.yaml")]
+    #[test]
+    fn lzrs_recompress(compressed_path_str: String) {
+        // The files are LZRS streams with their size header as stored by the game
+        let compressed = std::fs::read(compressed_path_str).unwrap();
+        let decompressed =
+            lzrs_decompress_data_with_header_parser(&mut Cursor::new(&compressed), Endian::Little)
+                .unwrap();
+        let recompressed = lzrs_compress_data_with_header(&decompressed, Endian::Little).unwrap();
+        assert_eq!(recompressed, compressed);
+    }
 }